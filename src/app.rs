@@ -1,18 +1,36 @@
 use std::cell::RefCell;
-use std::collections::{BTreeMap};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 use ratatui_widgets::list::ListState;
 use std::error::Error;
 use std::ops::Not;
+use std::path::PathBuf;
 use std::rc::Rc;
-use rand::seq::SliceRandom;
+use std::time::Instant;
+use rand::seq::{IndexedRandom, SliceRandom};
 use ratatui_widgets::scrollbar::ScrollbarState;
 use ratatui_widgets::table::TableState;
 use strum::{EnumIter, EnumMessage, VariantArray};
+use ratatui::crossterm::event::Event;
+use ratatui::layout::Position;
+use tui_input::backend::crossterm::EventHandler;
 use tui_input::Input;
 use crate::practice;
-use crate::practice::{ANSWER_REVEAL_PENALTY, CLUE_REVEAL_PENALTY};
+use crate::practice::{AnswerMode, AttemptResult, CharacterDiff, DiacriticsMode, Direction, DirectionSetting, GradingSettings, IgnoredSymbols, MasteryRound, ANSWER_OPTION_COUNT, DEFAULT_MASTERY_STREAK, MAX_MASTERY_STREAK};
+use crate::blitz::{Blitz, BLITZ_DURATION};
+use crate::config::{get_config_overrides_from, load_config, Config};
+use crate::keymap::{Action, Keymap};
+use crate::mouse;
+use crate::theme::{get_theme_name_from_env, Theme};
+use crate::mouse::{ClickTracker, MouseAreas};
+use crate::custom_deck::{CustomDeckStore, DeckEntry, DECKS_FILE_NAME};
 use crate::question::Question;
-use crate::question_parser::{collect_groups_from_multiple_paths, get_paths_from, read_all_questions_from_all_paths, QuestionGroup};
+use crate::search::{SearchMode, SearchQuery};
+use crate::group_tree;
+use crate::group_tree::{GroupTreeRow, GroupTreeRowKind};
+use crate::question_parser::{collect_groups_from_multiple_paths, get_group_naming_from, get_paths_from, read_all_questions_from_all_paths, ParseDiagnostic, QuestionGroup, Severity};
+use crate::session_store::{SessionState, SessionStore, SESSIONS_FILE_NAME};
+use crate::session_summary::{SessionStats, SessionSummary};
+use crate::stats_store::{StatsStore, STATS_FILE_NAME};
 
 #[derive(Debug, Clone)]
 pub enum AppPhase {
@@ -24,10 +42,15 @@ pub enum AppPhase {
 pub enum UiComponent {
     GroupSelector,
     QuestionSelector,
+    SearchInput,
+    DeckNameInput,
     PracticeControls,
     PracticeMain,
     ExitPopup,
     NotificationPopup,
+    ProblemsPopup,
+    SessionSummary,
+    HelpPopup,
 }
 
 #[derive(EnumIter, EnumMessage,     VariantArray)]
@@ -42,12 +65,41 @@ pub enum PracticeControlOptions {
     TryHardest5,
     #[strum(message="Try Hardest 10")]
     TryHardest10,
+    #[strum(message="Due Today")]
+    DueToday,
+    #[strum(message="Leitner Boxes")]
+    Leitner,
+    #[strum(message="Blitz (60s)")]
+    Blitz,
+    #[strum(message="Mastery")]
+    Mastery,
+    #[strum(message="Mastery Streak")]
+    MasteryStreak,
+    #[strum(message="Direction")]
+    Direction,
+    #[strum(message="Accents")]
+    Accents,
+    #[strum(message="Answers")]
+    AnswerMode,
     #[strum(message="Show Clue")]
     ShowClue,
     #[strum(message="Show Answer")]
     ShowAnswer
 }
 
+/// Defines how the questions of a round are ordered and when the round is refilled.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RoundMode {
+    /// Random order, reshuffled when the round is over.
+    Shuffled,
+    /// Spaced-repetition reviews of the questions that are due, the most urgent first.
+    DueToday,
+    /// Every question is asked until it is answered correctly a few times in a row, then the round ends.
+    Mastery,
+    /// The questions due in the Leitner boxes, the session ends when each of them is answered.
+    Leitner,
+}
+
 #[derive(Debug, Clone)]
 pub struct PopupDetails {
     pub title: String,
@@ -61,35 +113,98 @@ pub struct AppState {
     active_component: UiComponent,
     last_active_component: UiComponent,
     pub popup_details: PopupDetails,
+    /// The settings merged from the defaults, the config files and the commandline.
+    pub config: Config,
+    /// The keys bound to the actions, built from the `[keys]` section of the config.
+    pub keymap: Keymap,
+    pub theme: Theme,
+    stats_store: StatsStore,
+    deck_store: CustomDeckStore,
+    session_store: SessionStore,
+    /// The paths the questions are read from, the session is saved for them.
+    input_paths: Vec<PathBuf>,
+    /// Problems found in the question files so far.
+    pub diagnostics: Vec<ParseDiagnostic>,
+    pub problems_list_state: ListState,
 
     // Setup
     pub questions_by_groups: BTreeMap<String, QuestionGroup>,
-    pub group_names_by_indices: BTreeMap<usize, String>,
+    /// The groups shown in the group list, the ones matching the search if there is any.
+    shown_group_names: Vec<String>,
+    /// The visible rows of the group list, the shown groups and their folders.
+    pub group_tree_rows: Vec<GroupTreeRow>,
+    pub collapsed_folders: HashSet<String>,
     pub question_group_list_state: ListState,
     pub question_group_list_scrollbar_state: ScrollbarState,
     pub question_table_state: TableState,
     pub question_table_scrollbar_state: ScrollbarState,
     pub setup_body_left_size: Option<u16>,
+    pub direction_setting: DirectionSetting,
+    pub answer_mode: AnswerMode,
+    pub search_input: Input,
+    pub search_mode: SearchMode,
+    pub search_query: Option<SearchQuery>,
+    pub search_error: Option<String>,
+    /// Questions of the inactive groups, read for searching only.
+    search_index: BTreeMap<String, Vec<Rc<RefCell<Question>>>>,
+    /// Questions marked in the question table, practiced instead of every active question if any of them is active.
+    pub marked_questions: BTreeSet<DeckEntry>,
+    pub deck_name_input: Input,
 
     // Practice
     pub practice_controls_list_state: ListState,
     pub active_questions: Vec<Rc<RefCell<Question>>>,
     pub round_questions: Vec<Rc<RefCell<Question>>>,
     pub current_question_index: usize,
+    pub round_mode: RoundMode,
+    /// The direction the current question is asked in, resolved from the direction setting.
+    pub current_direction: Direction,
+    /// Penalties collected for the current question since it was displayed.
+    current_question_penalty: u16,
+    current_question_displayed_at: Instant,
+    /// The running timed practice, if any.
+    pub blitz: Option<Blitz>,
+    pub mastery_round: Option<MasteryRound>,
+    /// The number of correct answers in a row needed to master a question.
+    pub mastery_streak: u16,
     pub answer_input: Input,
-    pub is_previous_answer_successful: Option<bool>,
+    /// The answers to pick from in multiple-choice mode.
+    pub answer_options: Vec<String>,
+    pub previous_attempt_result: Option<AttemptResult>,
+    pub grading_settings: GradingSettings,
+    /// Every variant of the clue or answer revealed for the current question.
+    pub revealed_variants: Option<String>,
+    /// The last incorrect attempt compared to the closest answer.
+    pub answer_diff: Option<Vec<CharacterDiff>>,
     pub practice_body_left_size: Option<u16>,
+    session_stats: SessionStats,
+
+    // Summary
+    pub session_summary: Option<SessionSummary>,
+    pub session_summary_scroll: u16,
+
+    // Help
+    pub help_scroll: u16,
+
+    // Mouse
+    pub mouse_areas: MouseAreas,
+    click_tracker: ClickTracker,
+    is_dragging_divider: bool,
 }
 
 impl AppState {
     pub fn new(args: Vec<String>) -> Self {
-        let paths_from_program_args = get_paths_from(args);
+        let (config_overrides, args) = get_config_overrides_from(args);
+        let (group_naming, path_args) = get_group_naming_from(args);
+        let paths_from_program_args = get_paths_from(path_args);
+        let (config, config_errors) = load_config(&paths_from_program_args, &config_overrides);
+        let mut diagnostics = vec![];
+        let questions_by_groups =
+            collect_groups_from_multiple_paths(paths_from_program_args.clone(), group_naming, &mut diagnostics);
 
-        // Workaround to map the indices of groups,
-        // as RataTUI's List widget implementation can only return the index of a selected group.
-        // BTreeMaps guarantee the fix order of groups in the main map to match the index map.
-        let (questions_by_groups, group_names_by_indices) =
-            collect_groups_from_multiple_paths(paths_from_program_args);
+        let (stats_store, stats_store_error) = StatsStore::load_default(STATS_FILE_NAME, "Practice stats");
+        let (deck_store, deck_store_error) = CustomDeckStore::load_default(DECKS_FILE_NAME, "Custom decks");
+        let (session_store, session_store_error) = SessionStore::load_default(SESSIONS_FILE_NAME, "Selected groups and options");
 
         let mut app = Self {
             active_component: UiComponent::GroupSelector,
@@ -100,41 +215,158 @@ impl AppState {
                 width: 37,
                 height: 6,
             },
+            grading_settings: GradingSettings {
+                typo_tolerance: config.matching.typo_tolerance,
+                ignored_symbols: IgnoredSymbols::new(&config.matching.ignored_symbols).unwrap_or_default(),
+                ..GradingSettings::default()
+            },
+            keymap: Keymap::new(&config.keys).unwrap_or_default(),
+            theme: Theme::new(get_theme_name_from_env(config.appearance.theme)),
+            config,
+            stats_store,
+            deck_store,
+            session_store,
+            input_paths: paths_from_program_args,
+            diagnostics: vec![],
+            problems_list_state: ListState::default(),
 
             // Setup
             questions_by_groups,
-            group_names_by_indices,
+            shown_group_names: vec![],
+            group_tree_rows: vec![],
+            collapsed_folders: HashSet::new(),
             question_group_list_state: ListState::default(),
             question_group_list_scrollbar_state: ScrollbarState::default(),
             question_table_state: TableState::default(),
             question_table_scrollbar_state: ScrollbarState::default(),
             setup_body_left_size: None,
+            direction_setting: DirectionSetting::default(),
+            answer_mode: AnswerMode::default(),
+            search_input: Input::default(),
+            search_mode: SearchMode::default(),
+            search_query: None,
+            search_error: None,
+            search_index: BTreeMap::new(),
+            marked_questions: BTreeSet::new(),
+            deck_name_input: Input::default(),
 
             // Practice
             practice_controls_list_state: ListState::default(),
             active_questions: vec![],
             round_questions: vec![],
             current_question_index: 0,
+            round_mode: RoundMode::Shuffled,
+            current_direction: Direction::default(),
+            current_question_penalty: 0,
+            current_question_displayed_at: Instant::now(),
+            blitz: None,
+            mastery_round: None,
+            mastery_streak: DEFAULT_MASTERY_STREAK,
             answer_input: Input::default(),
-            is_previous_answer_successful: None,
+            answer_options: vec![],
+            previous_attempt_result: None,
+            revealed_variants: None,
+            answer_diff: None,
             practice_body_left_size: None,
+            session_stats: SessionStats::default(),
+
+            // Summary
+            session_summary: None,
+            session_summary_scroll: 0,
+
+            // Help
+            help_scroll: 0,
+
+            // Mouse
+            mouse_areas: MouseAreas::default(),
+            click_tracker: ClickTracker::default(),
+            is_dragging_divider: false,
         };
 
         app.question_group_list_state.select_first();
         app.question_table_state.select_first();
         app.practice_controls_list_state.select_first();
+        let deck_names: Vec<String> = app.deck_store.get_decks().keys().cloned().collect();
+        for deck_name in deck_names {
+            app.setup_add_deck_group(&deck_name, &mut diagnostics);
+        }
+        app.setup_update_shown_groups();
+        let store_errors: Vec<String> = [stats_store_error, deck_store_error, session_store_error].into_iter().flatten().collect();
+        let mut startup_errors = vec![];
+        if !store_errors.is_empty() {
+            startup_errors.push(store_errors.join(" "));
+        }
+        if !config_errors.is_empty() {
+            startup_errors.push("Ignored invalid settings:".to_string());
+            startup_errors.extend(config_errors);
+        }
+        if !startup_errors.is_empty() {
+            let _ = app.open_notification_popup(&startup_errors.join("\n"));
+        }
+        if let Some(state) = app.session_store.get(&app.input_paths).cloned() {
+            app.restore_session_state(state);
+        }
+        app.add_diagnostics(diagnostics);
         app
     }
 
+    /// Restores the selected groups, pane sizes and practice options of the last run with the same input paths.
+    fn restore_session_state(&mut self, state: SessionState) {
+        for group_id in &state.active_groups {
+            if let Some(group_name) = self.get_group_name_by_id(group_id) {
+                self.setup_set_group_active_status(&group_name, true);
+            }
+        }
+        self.setup_body_left_size = state.setup_body_left_size;
+        self.practice_body_left_size = state.practice_body_left_size;
+        self.direction_setting = state.direction_setting;
+        self.answer_mode = state.answer_mode;
+        self.grading_settings.diacritics_mode = state.diacritics_mode;
+        if let Some(mastery_streak) = state.mastery_streak.filter(|streak| (1..=MAX_MASTERY_STREAK).contains(streak)) {
+            self.mastery_streak = mastery_streak;
+        }
+    }
+
+    /// Returns the name of the group with the identity, or with the name if it was saved by an earlier version.
+    fn get_group_name_by_id(&self, group_id: &str) -> Option<String> {
+        self.questions_by_groups.iter()
+            .find(|(_, group)| group.id == group_id)
+            .or_else(|| self.questions_by_groups.get_key_value(group_id))
+            .map(|(group_name, _)| group_name.clone())
+    }
+
+    fn get_session_state(&self) -> SessionState {
+        SessionState {
+            active_groups: self.questions_by_groups.iter()
+                .filter(|(_, group)| group.is_active)
+                .map(|(_, group)| group.id.clone())
+                .collect(),
+            setup_body_left_size: self.setup_body_left_size,
+            practice_body_left_size: self.practice_body_left_size,
+            direction_setting: self.direction_setting,
+            answer_mode: self.answer_mode,
+            diacritics_mode: self.grading_settings.diacritics_mode,
+            mastery_streak: Some(self.mastery_streak),
+        }
+    }
+
+    fn save_session_state(&mut self) -> Result<(), Box<dyn Error>> {
+        self.session_store.update(&self.input_paths, self.get_session_state());
+        self.session_store.save()
+    }
+
     pub fn get_app_phase_for_active_component(&self) -> AppPhase {
         self.get_app_phase_for_component(&self.active_component)
     }
 
     fn get_app_phase_for_component(&self, component: &UiComponent) -> AppPhase {
         match component {
-            UiComponent::GroupSelector | UiComponent::QuestionSelector => AppPhase::Setup,
-            UiComponent::PracticeControls | UiComponent::PracticeMain => AppPhase::Practice,
-            UiComponent::ExitPopup | UiComponent::NotificationPopup => {
+            UiComponent::GroupSelector
+            | UiComponent::QuestionSelector
+            | UiComponent::SearchInput
+            | UiComponent::DeckNameInput => AppPhase::Setup,
+            UiComponent::PracticeControls | UiComponent::PracticeMain | UiComponent::SessionSummary => AppPhase::Practice,
+            UiComponent::ExitPopup | UiComponent::NotificationPopup | UiComponent::ProblemsPopup | UiComponent::HelpPopup => {
                 // Defined by the component the popup was opened from.
                 self.get_app_phase_for_component(&self.last_active_component)
             },
@@ -161,45 +393,370 @@ impl AppState {
     }
 
     pub fn setup_toggle_group_active_status_and_load_questions(&mut self) -> Result<(), Box<dyn Error>> {
-        let selected_group_name = self.setup_get_selected_group_name().clone();
-        let selected_group_op = self.questions_by_groups.get_mut(&selected_group_name);
-        if selected_group_op.is_none() {
+        if let Some(row) = self.setup_get_selected_tree_row().filter(|row| row.is_folder()) {
+            let folder = row.path.clone();
+            return self.setup_toggle_folder_active_status(&folder);
+        }
+        let Some(selected_group_name) = self.setup_get_selected_group_name() else { return Ok(()) };
+        let Some(is_active) = self.questions_by_groups.get(&selected_group_name).map(|group| group.is_active) else {
             return self.open_notification_popup(
                 format!("Error: Selected group not found: '{}'", selected_group_name).as_str()
             );
+        };
+        self.setup_set_group_active_status(&selected_group_name, is_active.not());
+        Ok(())
+    }
+
+    /// Activates every shown group in the folder and its subfolders, or deactivates them if all of them are active.
+    fn setup_toggle_folder_active_status(&mut self, folder: &str) -> Result<(), Box<dyn Error>> {
+        let group_names: Vec<String> = self.shown_group_names.iter()
+            .filter(|group_name| group_tree::is_in_folder(group_name, folder))
+            .cloned()
+            .collect();
+        let is_every_group_active = group_names.iter()
+            .all(|group_name| self.questions_by_groups.get(group_name).is_some_and(|group| group.is_active));
+        for group_name in group_names {
+            self.setup_set_group_active_status(&group_name, !is_every_group_active);
         }
-        let selected_group = selected_group_op.unwrap();
+        Ok(())
+    }
 
-        // Toggle active state
-        selected_group.is_active = selected_group.is_active.not();
+    /// Activates every group matching the search, nothing without a search.
+    pub fn setup_activate_shown_groups(&mut self) -> Result<(), Box<dyn Error>> {
+        if self.search_query.is_none() {
+            return Ok(());
+        }
+        for group_name in self.shown_group_names.clone() {
+            self.setup_set_group_active_status(&group_name, true);
+        }
+        Ok(())
+    }
 
-        if selected_group.is_active {
-            // load questions
-            let mut questions = read_all_questions_from_all_paths(&selected_group.paths);
-            selected_group.questions.append(&mut questions);
-        } else {
-            // clear questions
-            selected_group.questions.clear();
+    /// Collapses the selected folder, or selects the folder of the selected row.
+    pub fn setup_collapse_selected_folder(&mut self) -> Result<(), Box<dyn Error>> {
+        let Some(row) = self.setup_get_selected_tree_row().cloned() else { return Ok(()) };
+        let is_expanded_folder = row.kind == GroupTreeRowKind::Folder { is_expanded: true };
+        if is_expanded_folder && self.search_query.is_none() {
+            self.collapsed_folders.insert(row.path);
+            self.setup_update_group_tree_rows();
+        } else if let Some(parent_folder) = group_tree::get_parent_folder(&row.path) {
+            let parent_index = self.group_tree_rows.iter().position(|row| row.path == parent_folder);
+            self.question_group_list_state.select(parent_index);
+        }
+        Ok(())
+    }
+
+    /// Expands the selected folder, or switches to the question table if a group is selected.
+    pub fn setup_expand_selected_folder(&mut self) -> Result<(), Box<dyn Error>> {
+        let Some(row) = self.setup_get_selected_tree_row().cloned() else { return Ok(()) };
+        match row.kind {
+            GroupTreeRowKind::Folder { is_expanded: false } => {
+                self.collapsed_folders.remove(&row.path);
+                self.setup_update_group_tree_rows();
+                Ok(())
+            },
+            GroupTreeRowKind::Folder { is_expanded: true } => Ok(()),
+            GroupTreeRowKind::Group => self.setup_toggle_panes(),
+        }
+    }
+
+    fn setup_set_group_active_status(&mut self, group_name: &str, is_active: bool) {
+        let Some(group) = self.questions_by_groups.get(group_name) else { return };
+        if group.is_active == is_active {
+            return;
+        }
+        let mut diagnostics = vec![];
+        // load questions, or clear them
+        let questions = if is_active { self.setup_read_group_questions(group_name, &mut diagnostics) } else { vec![] };
+        if let Some(group) = self.questions_by_groups.get_mut(group_name) {
+            group.is_active = is_active;
+            group.questions = questions;
+        }
+        self.add_diagnostics(diagnostics);
+    }
+
+    /// Reads the questions of a group from its files, or the picked questions from their groups for a custom deck.
+    fn setup_read_group_questions(&self, group_name: &str, diagnostics: &mut Vec<ParseDiagnostic>) -> Vec<Rc<RefCell<Question>>> {
+        let Some(group) = self.questions_by_groups.get(group_name) else { return vec![] };
+        let Some(deck_entries) = &group.deck else {
+            return read_all_questions_from_all_paths(group_name, &group.paths, &self.stats_store, diagnostics);
+        };
+        // The picked questions keep their own groups, so they share the stats with them.
+        let source_group_names: BTreeSet<String> = deck_entries.iter()
+            .filter_map(|entry| self.get_group_name_by_id(&entry.group))
+            .collect();
+        source_group_names.into_iter()
+            .filter_map(|source_group_name| self.questions_by_groups.get(&source_group_name)
+                .map(|source_group| (source_group_name, source_group)))
+            .flat_map(|(source_group_name, source_group)| read_all_questions_from_all_paths(
+                &source_group_name,
+                &source_group.paths,
+                &self.stats_store,
+                diagnostics,
+            ))
+            .filter(|question| {
+                let question = question.borrow();
+                let entry = DeckEntry::from_question(&question);
+                // Decks saved by earlier versions reference the groups by name.
+                deck_entries.contains(&entry) || deck_entries.contains(&DeckEntry { group: question.group.clone(), ..entry })
+            })
+            .collect()
+    }
+
+    /// Adds a saved custom deck to the groups, unless a group read from files has the same name.
+    fn setup_add_deck_group(&mut self, deck_name: &str, diagnostics: &mut Vec<ParseDiagnostic>) {
+        let Some(deck_entries) = self.deck_store.get_decks().get(deck_name) else { return };
+        match self.questions_by_groups.get_mut(deck_name) {
+            Some(group) if group.deck.is_some() => group.deck = Some(deck_entries.clone()),
+            Some(_) => diagnostics.push(ParseDiagnostic {
+                file: self.deck_store.get_path().map(|path| path.display().to_string()).unwrap_or_default(),
+                line: None,
+                column: None,
+                severity: Severity::Warning,
+                message: format!("Skipping custom deck '{deck_name}', a group with the same name exists"),
+            }),
+            None => {
+                self.questions_by_groups.insert(deck_name.to_string(), QuestionGroup {
+                    id: deck_name.to_string(),
+                    deck: Some(deck_entries.clone()),
+                    ..QuestionGroup::default()
+                });
+            },
+        }
+    }
+
+    /// Cycles the diacritics mode override of the selected group, ending with the global mode.
+    pub fn setup_toggle_group_diacritics_mode(&mut self) -> Result<(), Box<dyn Error>> {
+        let Some(selected_group_name) = self.setup_get_selected_group_name() else { return Ok(()) };
+        if let Some(group) = self.questions_by_groups.get_mut(&selected_group_name) {
+            group.diacritics_mode = match group.diacritics_mode {
+                None => Some(DiacriticsMode::Strict),
+                Some(DiacriticsMode::PartialError) => None,
+                Some(mode) => Some(mode.next()),
+            };
         }
         Ok(())
     }
 
+    pub fn toggle_diacritics_mode(&mut self) -> Result<(), Box<dyn Error>> {
+        self.grading_settings.diacritics_mode = self.grading_settings.diacritics_mode.next();
+        Ok(())
+    }
+
     pub fn setup_get_questions_for_selected_group(&mut self) -> Vec<Rc<RefCell<Question>>> {
-        let selected_group_name = self.setup_get_selected_group_name().clone();
-        let group_op = self.questions_by_groups.get_mut(&selected_group_name);
-        if group_op.is_none() { return vec![] }
-        let group = group_op.unwrap();
-        if group.is_active {
-            group.questions.to_vec()
+        let Some(selected_group_name) = self.setup_get_selected_group_name() else { return vec![] };
+        self.setup_get_shown_questions_for_group(&selected_group_name)
+    }
+
+    /// Returns the questions of an active group, only the ones matching the search unless the group name matches it.
+    fn setup_get_shown_questions_for_group(&self, group_name: &str) -> Vec<Rc<RefCell<Question>>> {
+        let Some(group) = self.questions_by_groups.get(group_name) else { return vec![] };
+        if !group.is_active {
+            return vec![];
+        }
+        match &self.search_query {
+            Some(query) if !query.is_match(group_name) => group.questions.iter()
+                .filter(|question| query.matches_question(&question.borrow()))
+                .cloned()
+                .collect(),
+            _ => group.questions.to_vec(),
+        }
+    }
+
+    fn setup_get_selected_tree_row(&self) -> Option<&GroupTreeRow> {
+        let selected_row_pos = self.question_group_list_state.selected().unwrap_or(0);
+        self.group_tree_rows.get(selected_row_pos)
+    }
+
+    fn setup_get_selected_group_name(&self) -> Option<String> {
+        self.setup_get_selected_tree_row()
+            .filter(|row| !row.is_folder())
+            .map(|row| row.path.clone())
+    }
+
+    pub fn setup_is_question_marked(&self, question: &Question) -> bool {
+        self.marked_questions.contains(&DeckEntry::from_question(question))
+    }
+
+    pub fn setup_toggle_selected_question_mark(&mut self) -> Result<(), Box<dyn Error>> {
+        let questions = self.setup_get_questions_for_selected_group();
+        let selected_index = self.question_table_state.selected().unwrap_or(0);
+        let Some(question) = questions.get(selected_index) else { return Ok(()) };
+        let entry = DeckEntry::from_question(&question.borrow());
+        if !self.marked_questions.remove(&entry) {
+            self.marked_questions.insert(entry);
+        }
+        Ok(())
+    }
+
+    /// Inverts the marks of the questions shown in the question table.
+    pub fn setup_invert_question_marks(&mut self) -> Result<(), Box<dyn Error>> {
+        for question in self.setup_get_questions_for_selected_group() {
+            let entry = DeckEntry::from_question(&question.borrow());
+            if !self.marked_questions.remove(&entry) {
+                self.marked_questions.insert(entry);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn setup_open_deck_name_input(&mut self) -> Result<(), Box<dyn Error>> {
+        if self.marked_questions.is_empty() {
+            return self.open_notification_popup("Mark questions with [Space] to save them as a custom deck.");
+        }
+        self.deck_name_input.reset();
+        self.set_active_component(UiComponent::DeckNameInput);
+        Ok(())
+    }
+
+    pub fn deck_name_handle_input(&mut self, event: &Event) -> Result<(), Box<dyn Error>> {
+        self.deck_name_input.handle_event(event);
+        Ok(())
+    }
+
+    /// Saves the marked questions as a custom deck, replacing an earlier deck with the same name.
+    pub fn deck_name_confirm(&mut self) -> Result<(), Box<dyn Error>> {
+        let deck_name = self.deck_name_input.value().trim().to_string();
+        if deck_name.is_empty() {
+            return Ok(());
+        }
+        if self.questions_by_groups.get(&deck_name).is_some_and(|group| group.deck.is_none()) {
+            return self.open_notification_popup(&format!("A group named '{deck_name}' already exists."));
+        }
+        self.deck_store.insert(&deck_name, self.marked_questions.clone());
+        let mut diagnostics = vec![];
+        self.setup_add_deck_group(&deck_name, &mut diagnostics);
+        self.add_diagnostics(diagnostics);
+        // Reload the questions of an active deck and forget the ones read for searching.
+        self.search_index.remove(&deck_name);
+        if self.questions_by_groups.get(&deck_name).is_some_and(|group| group.is_active) {
+            self.setup_set_group_active_status(&deck_name, false);
+            self.setup_set_group_active_status(&deck_name, true);
+        }
+        self.setup_update_shown_groups();
+        self.close_popup()?;
+        match self.deck_store.save() {
+            Ok(_) => self.open_notification_popup(&format!("Saved {} questions as '{deck_name}'.", self.marked_questions.len())),
+            Err(e) => self.open_notification_popup(&format!("Error: Cannot save custom decks: {e}")),
+        }
+    }
+
+    pub fn deck_name_cancel(&mut self) -> Result<(), Box<dyn Error>> {
+        self.close_popup()
+    }
+
+    pub fn setup_open_search(&mut self) -> Result<(), Box<dyn Error>> {
+        self.set_active_component(UiComponent::SearchInput);
+        Ok(())
+    }
+
+    pub fn search_handle_input(&mut self, event: &Event) -> Result<(), Box<dyn Error>> {
+        self.search_input.handle_event(event);
+        self.search_apply();
+        Ok(())
+    }
+
+    pub fn search_toggle_mode(&mut self) -> Result<(), Box<dyn Error>> {
+        self.search_mode = self.search_mode.next();
+        self.search_apply();
+        Ok(())
+    }
+
+    /// Keeps the search results and returns to the pane the search was started from.
+    pub fn search_confirm(&mut self) -> Result<(), Box<dyn Error>> {
+        self.close_popup()
+    }
+
+    pub fn search_cancel(&mut self) -> Result<(), Box<dyn Error>> {
+        self.search_input.reset();
+        self.search_apply();
+        self.close_popup()
+    }
+
+    fn search_apply(&mut self) {
+        let text = self.search_input.value();
+        self.search_error = None;
+        if text.is_empty() {
+            self.search_query = None;
         } else {
-            vec![]
+            match SearchQuery::new(text, self.search_mode) {
+                Ok(query) => self.search_query = Some(query),
+                // Keep the previous results while the expression is being typed.
+                Err(_) => {
+                    self.search_error = Some("Invalid regular expression".to_string());
+                    return;
+                },
+            }
+        }
+        self.setup_update_shown_groups();
+        self.question_group_list_state.select_first();
+        self.question_table_state.select_first();
+    }
+
+    /// Updates the group list after the search or the groups changed.
+    fn setup_update_shown_groups(&mut self) {
+        let group_names: Vec<String> = self.questions_by_groups.keys().cloned().collect();
+        self.shown_group_names = group_names.into_iter()
+            .filter(|group_name| self.search_group_matches(group_name))
+            .collect();
+        self.setup_update_group_tree_rows();
+    }
+
+    /// Updates the rows of the group list after folders were collapsed or expanded.
+    fn setup_update_group_tree_rows(&mut self) {
+        // The search hits are never hidden in collapsed folders.
+        self.group_tree_rows = group_tree::get_tree_rows(&self.shown_group_names, &self.collapsed_folders, self.search_query.is_some());
+    }
+
+    /// Checks the group name, questions and answers of the group against the search.
+    fn search_group_matches(&mut self, group_name: &str) -> bool {
+        let Some(query) = self.search_query.clone() else { return true };
+        if query.is_match(group_name) {
+            return true;
+        }
+        let Some(group) = self.questions_by_groups.get(group_name) else { return false };
+        if group.is_active {
+            return group.questions.iter().any(|question| query.matches_question(&question.borrow()));
+        }
+        if !self.search_index.contains_key(group_name) {
+            // Problems are reported when the group is activated, so they are ignored here.
+            let questions = self.setup_read_group_questions(group_name, &mut vec![]);
+            self.search_index.insert(group_name.to_string(), questions);
         }
+        self.search_index.get(group_name)
+            .is_some_and(|questions| questions.iter().any(|question| query.matches_question(&question.borrow())))
     }
 
-    fn setup_get_selected_group_name(&mut self) -> &String {
-        let selected_group_pos = self.question_group_list_state.selected().unwrap_or(0);
-        self.group_names_by_indices.get(&selected_group_pos)
-            .unwrap_or_else(|| panic!("Can't find selected group at position {selected_group_pos}"))
+    /// Selects the next or previous search hit: a matching question of an active group, or a matching inactive group.
+    pub fn setup_jump_to_search_hit(&mut self, is_forward: bool) -> Result<(), Box<dyn Error>> {
+        if self.search_query.is_none() {
+            return Ok(());
+        }
+        let mut hits: Vec<(usize, Option<usize>)> = vec![];
+        for (group_index, row) in self.group_tree_rows.iter().enumerate().filter(|(_, row)| !row.is_folder()) {
+            match self.setup_get_shown_questions_for_group(&row.path).len() {
+                0 => hits.push((group_index, None)),
+                question_count => hits.extend((0..question_count).map(|row| (group_index, Some(row)))),
+            }
+        }
+        let current_group = self.question_group_list_state.selected().unwrap_or(0);
+        let current = match self.active_component {
+            UiComponent::QuestionSelector => (current_group, self.question_table_state.selected()),
+            _ => (current_group, None),
+        };
+        let next_hit = if is_forward {
+            hits.iter().find(|hit| **hit > current).or(hits.first())
+        } else {
+            hits.iter().rev().find(|hit| **hit < current).or(hits.last())
+        };
+        let Some((group_index, row)) = next_hit.cloned() else { return Ok(()) };
+        self.question_group_list_state.select(Some(group_index));
+        self.question_table_state.select(row.or(Some(0)));
+        let component = if row.is_some() { UiComponent::QuestionSelector } else { UiComponent::GroupSelector };
+        if self.active_component != component {
+            self.set_active_component(component);
+        }
+        Ok(())
     }
 
     pub fn setup_previous_question(&mut self) -> Result<(), Box<dyn Error>> {
@@ -225,11 +782,41 @@ impl AppState {
     }
 
     pub fn setup_navigate_to_practice(&mut self) -> Result<(), Box<dyn Error>> {
-        self.active_questions = self.practice_get_all_active_questions();
+        let all_active_questions = self.practice_get_all_active_questions();
+        let marked_questions: Vec<Rc<RefCell<Question>>> = all_active_questions.iter()
+            .filter(|question| self.setup_is_question_marked(&question.borrow()))
+            .cloned()
+            .collect();
+        let questions = if marked_questions.is_empty() { all_active_questions } else { marked_questions };
+        if questions.is_empty() {
+            return self.open_notification_popup("No questions to practice, select groups with questions first.");
+        }
+        self.active_questions = questions;
+        // Every practice is a new session, the round stats are summarized at its end.
+        self.active_questions.iter()
+            .for_each(|question| question.borrow_mut().reset_round_stats());
+        self.session_stats = SessionStats::default();
         self.round_questions = self.active_questions.clone();
+        self.round_mode = RoundMode::Shuffled;
         self.practice_shuffle_questions();
         self.set_active_component(UiComponent::PracticeMain);
-        self.practice_reset_answer()
+        self.practice_reset_answer()?;
+        match self.save_session_state() {
+            Ok(_) => Ok(()),
+            // Saving is not critical for the practice to start.
+            Err(e) => self.open_notification_popup(&format!("Error: Cannot save the selected groups and options: {e}")),
+        }
+    }
+
+    pub fn setup_toggle_direction(&mut self) -> Result<(), Box<dyn Error>> {
+        self.direction_setting = self.direction_setting.next();
+        Ok(())
+    }
+
+    pub fn toggle_answer_mode(&mut self) -> Result<(), Box<dyn Error>> {
+        self.answer_mode = self.answer_mode.next();
+        self.practice_generate_answer_options();
+        Ok(())
     }
 
     pub fn setup_increase_body_left_size(&mut self) -> Result<(), Box<dyn Error>> {
@@ -250,6 +837,7 @@ impl AppState {
     }
 
     pub fn practice_navigate_to_setup(&mut self) -> Result<(), Box<dyn Error>> {
+        self.blitz = None;
         self.set_active_component(UiComponent::GroupSelector);
         self.answer_input.reset();
         self.practice_reset_answer()
@@ -282,98 +870,432 @@ impl AppState {
     }
 
     fn practice_filter_data_to_hardest_in_round(&mut self, limit: usize) -> Result<(), Box<dyn Error>> {
-        self.round_questions = practice::get_hardest_questions_in_round(&self.active_questions, limit);
+        self.round_questions = practice::get_hardest_questions_in_round(
+            &self.active_questions,
+            limit,
+            self.direction_setting.get_directions(),
+        );
+        self.round_mode = RoundMode::Shuffled;
         self.practice_shuffle_questions();
         self.practice_reset_answer()
     }
 
     fn practice_reset_round_question_filters(&mut self) -> Result<(), Box<dyn Error>> {
         self.round_questions = self.active_questions.clone();
+        self.round_mode = RoundMode::Shuffled;
         self.practice_shuffle_questions();
         self.practice_reset_answer()
     }
 
-    fn practice_get_all_active_questions(&mut self) -> Vec<Rc<RefCell<Question>>> {
+    fn practice_filter_data_to_due_today(&mut self) -> Result<(), Box<dyn Error>> {
+        let due_questions = practice::get_due_questions(
+            &self.active_questions,
+            practice::get_current_day(),
+            self.direction_setting.get_directions(),
+        );
+        if due_questions.is_empty() {
+            return self.open_notification_popup("No questions are due for review today.");
+        }
+        self.round_questions = due_questions;
+        self.round_mode = RoundMode::DueToday;
+        self.current_question_index = 0;
+        self.practice_resolve_current_direction();
+        self.practice_reset_answer()
+    }
+
+    fn practice_toggle_direction(&mut self) -> Result<(), Box<dyn Error>> {
+        self.direction_setting = self.direction_setting.next();
+        self.practice_resolve_current_direction();
+        self.practice_reset_answer()
+    }
+
+    /// Picks the direction of the current question from the ones allowed by the direction setting.
+    fn practice_resolve_current_direction(&mut self) {
+        let Some(question) = self.round_questions.get(self.current_question_index) else { return };
+        let mut directions = self.direction_setting.get_directions().to_vec();
+        let due_directions = match self.round_mode {
+            RoundMode::DueToday => practice::get_due_directions(&question.borrow(), practice::get_current_day(), &directions),
+            RoundMode::Leitner => practice::get_leitner_due_directions(&question.borrow(), &directions),
+            RoundMode::Shuffled | RoundMode::Mastery => vec![],
+        };
+        if !due_directions.is_empty() {
+            directions = due_directions;
+        }
+        self.current_direction = *directions.choose(&mut rand::rng()).unwrap_or(&Direction::Forward);
+        self.practice_generate_answer_options();
+        self.current_question_displayed_at = Instant::now();
+        if let Some(blitz) = &mut self.blitz {
+            blitz.start_card(self.current_question_displayed_at);
+        }
+    }
+
+    fn practice_generate_answer_options(&mut self) {
+        self.answer_options = match (self.answer_mode, self.round_questions.get(self.current_question_index)) {
+            (AnswerMode::MultipleChoice, Some(question)) => practice::get_answer_options(
+                &question.borrow(),
+                self.current_direction,
+                &self.active_questions,
+                ANSWER_OPTION_COUNT,
+                &self.grading_settings.ignored_symbols,
+            ),
+            _ => vec![],
+        };
+    }
+
+    pub fn practice_get_all_active_questions(&self) -> Vec<Rc<RefCell<Question>>> {
+        // A question can be active in its group and in a custom deck too, but it's practiced once.
+        let mut seen_questions = HashSet::new();
         self.questions_by_groups.values()
             .filter(|group| group.is_active)
             .flat_map(|group| group.questions.iter().cloned())
+            .filter(|question| seen_questions.insert(DeckEntry::from_question(&question.borrow())))
             .collect()
     }
 
     pub fn practice_activate_selected_control(&mut self) -> Result<(), Box<dyn Error>> {
         let selected_index = self.practice_controls_list_state.selected()
             .unwrap_or(0);
-        match PracticeControlOptions::VARIANTS[selected_index] {
-            PracticeControlOptions::EndPractice => self.practice_navigate_to_setup(),
+        let control = &PracticeControlOptions::VARIANTS[selected_index];
+        if matches!(
+            control,
+            PracticeControlOptions::EndPractice | PracticeControlOptions::ResetStats | PracticeControlOptions::TryAll
+            | PracticeControlOptions::TryHardest5 | PracticeControlOptions::TryHardest10 | PracticeControlOptions::DueToday
+            | PracticeControlOptions::Mastery | PracticeControlOptions::Leitner
+        ) {
+            // Starting another round ends the blitz.
+            self.blitz = None;
+        }
+        match control {
+            PracticeControlOptions::EndPractice => self.practice_end_session(),
             PracticeControlOptions::ResetStats => self.practice_reset_round_question_filters_and_stats(),
             PracticeControlOptions::TryHardest5 => self.practice_filter_data_to_hardest_in_round(5),
             PracticeControlOptions::TryHardest10 => self.practice_filter_data_to_hardest_in_round(10),
             PracticeControlOptions::TryAll => self.practice_reset_round_question_filters(),
+            PracticeControlOptions::DueToday => self.practice_filter_data_to_due_today(),
+            PracticeControlOptions::Leitner => self.practice_start_leitner_session(),
+            PracticeControlOptions::Blitz => self.practice_start_blitz(),
+            PracticeControlOptions::Mastery => self.practice_start_mastery_round(),
+            PracticeControlOptions::MasteryStreak => self.practice_toggle_mastery_streak(),
+            PracticeControlOptions::Direction => self.practice_toggle_direction(),
+            PracticeControlOptions::Accents => self.toggle_diacritics_mode(),
+            PracticeControlOptions::AnswerMode => self.toggle_answer_mode(),
             PracticeControlOptions::ShowClue => self.practice_show_clue(),
             PracticeControlOptions::ShowAnswer => self.practice_show_answer(),
         }
     }
 
     fn practice_show_clue(&mut self) -> Result<(), Box<dyn Error>> {
-        let question = self.practice_get_current_question_in_round().clone();
-        let clue = practice::reveal_clue(question.clone());
-        self.answer_input = Input::new(clue);
-        question.borrow_mut().increment_error_count(CLUE_REVEAL_PENALTY);
-        Ok(())
+        let Some(question) = self.practice_get_current_question_in_round() else { return Ok(()) };
+        let clues = practice::reveal_clues(question.clone(), self.current_direction);
+        self.answer_input = Input::new(clues[0].clone());
+        self.revealed_variants = Some(format!("Clue: {}", clues.join(" / ")));
+        self.session_stats.record_clue_reveal();
+        self.practice_penalize_current_question(question.clone(), self.config.penalties.clue_reveal);
+        self.practice_save_stats(question)
     }
 
     fn practice_show_answer(&mut self) -> Result<(), Box<dyn Error>> {
-        let question = self.practice_get_current_question_in_round();
-        let answer = practice::reveal_answer(question.clone());
-        self.answer_input = Input::new(answer);
-        question.borrow_mut().increment_error_count(ANSWER_REVEAL_PENALTY);
-        Ok(())
+        let Some(question) = self.practice_get_current_question_in_round() else { return Ok(()) };
+        let answers = practice::reveal_answers(question.clone(), self.current_direction);
+        self.answer_input = Input::new(answers[0].clone());
+        self.revealed_variants = Some(format!("Answer: {}", answers.join(" / ")));
+        self.session_stats.record_answer_reveal();
+        self.practice_penalize_current_question(question.clone(), self.config.penalties.answer_reveal);
+        self.practice_save_stats(question)
     }
 
     pub fn practice_validate_attempt(&mut self) -> Result<(), Box<dyn Error>> {
-        let question = self.practice_get_current_question_in_round().clone();
-        if practice::is_attempt_successful(self.answer_input.value(), question.clone()) {
-            question.borrow_mut().increment_correct_count(1);
-            self.is_previous_answer_successful = Some(true);
+        let Some(question) = self.practice_get_current_question_in_round() else { return Ok(()) };
+        let grading_settings = self.practice_get_grading_settings(&question.borrow());
+        let attempt = self.answer_input.value().to_string();
+        self.practice_grade_attempt(question, &attempt, &grading_settings)
+    }
+
+    pub fn practice_choose_answer_option(&mut self, index: usize) -> Result<(), Box<dyn Error>> {
+        let Some(option) = self.answer_options.get(index).cloned() else { return Ok(()) };
+        let Some(question) = self.practice_get_current_question_in_round() else { return Ok(()) };
+        // The options are picked as they are, so typos and accents are never the reason of a mismatch.
+        let grading_settings = GradingSettings {
+            typo_tolerance: 0.0,
+            diacritics_mode: DiacriticsMode::Strict,
+            ..self.grading_settings.clone()
+        };
+        self.practice_grade_attempt(question, &option, &grading_settings)
+    }
+
+    fn practice_grade_attempt(&mut self, question: Rc<RefCell<Question>>, attempt: &str, grading_settings: &GradingSettings) -> Result<(), Box<dyn Error>> {
+        let direction = self.current_direction;
+        let result = practice::grade_attempt(attempt, question.clone(), direction, grading_settings);
+        question.borrow_mut().record_response_time(direction, self.current_question_displayed_at.elapsed());
+        self.practice_penalize_current_question(question.clone(), result.get_penalty());
+        self.session_stats.record_attempt(&question.borrow(), result.is_accepted());
+        if result.is_accepted() {
+            question.borrow_mut().increment_correct_count(direction, 1);
+            if let Some(blitz) = &mut self.blitz {
+                blitz.correct_count += 1;
+            }
+            match self.round_mode {
+                RoundMode::DueToday => {
+                    let quality = practice::get_review_quality(self.current_question_penalty, &self.config.penalties);
+                    question.borrow_mut().get_schedule_mut(direction).review(quality, practice::get_current_timestamp());
+                },
+                RoundMode::Leitner => {
+                    question.borrow_mut().get_leitner_mut(direction).review(self.current_question_penalty == 0);
+                },
+                RoundMode::Shuffled | RoundMode::Mastery => {},
+            }
             self.answer_input.reset();
             self.practice_set_next_question_in_round()?;
-        } else {
-            question.borrow_mut().increment_error_count(1);
-            self.is_previous_answer_successful = Some(false);
+        } else if self.answer_mode == AnswerMode::Typing {
+            let ignored_symbols = &grading_settings.ignored_symbols;
+            self.answer_diff = practice::get_closest_answer(attempt, &question.borrow(), direction, ignored_symbols)
+                .map(|(_, closest_answer)| practice::get_character_diff(attempt, &closest_answer, ignored_symbols));
+        }
+        self.previous_attempt_result = Some(result);
+        self.practice_save_stats(question)
+    }
+
+    fn practice_start_leitner_session(&mut self) -> Result<(), Box<dyn Error>> {
+        let due_questions = practice::start_leitner_session(&self.active_questions, self.direction_setting.get_directions());
+        if due_questions.is_empty() {
+            return self.open_notification_popup("No questions are due in the Leitner boxes in this session.");
+        }
+        // The started session is saved, so it is counted once even if it is not finished.
+        for question in &self.active_questions {
+            self.stats_store.update(&question.borrow());
+        }
+        self.practice_write_stats()?;
+        self.round_questions = due_questions;
+        self.round_mode = RoundMode::Leitner;
+        self.practice_shuffle_questions();
+        self.practice_reset_answer()
+    }
+
+    fn practice_start_mastery_round(&mut self) -> Result<(), Box<dyn Error>> {
+        self.round_questions = self.active_questions.clone();
+        self.round_questions.shuffle(&mut rand::rng());
+        self.mastery_round = Some(MasteryRound::new(self.round_questions.clone(), self.mastery_streak));
+        self.round_mode = RoundMode::Mastery;
+        self.current_question_index = 0;
+        self.practice_resolve_current_direction();
+        self.practice_reset_answer()
+    }
+
+    /// Cycles the number of correct answers in a row needed to master a question, used by the next mastery round.
+    fn practice_toggle_mastery_streak(&mut self) -> Result<(), Box<dyn Error>> {
+        self.mastery_streak = self.mastery_streak % MAX_MASTERY_STREAK + 1;
+        Ok(())
+    }
+
+    fn practice_start_blitz(&mut self) -> Result<(), Box<dyn Error>> {
+        if self.active_questions.is_empty() {
+            return self.open_notification_popup("No questions to practice in a blitz.");
+        }
+        self.round_questions = self.active_questions.clone();
+        self.round_mode = RoundMode::Shuffled;
+        self.blitz = Some(Blitz::start(Instant::now()));
+        self.practice_shuffle_questions();
+        self.set_active_component(UiComponent::PracticeMain);
+        self.practice_reset_answer()
+    }
+
+    /// Updates the timers, called by the event loop even when there is no input.
+    pub fn practice_tick(&mut self) -> Result<(), Box<dyn Error>> {
+        let is_popup_open = self.is_popup_open();
+        let Some(blitz) = &mut self.blitz else { return Ok(()) };
+        let now = Instant::now();
+        // The time spent in the help or the exit confirmation does not count.
+        if is_popup_open {
+            blitz.pause(now);
+            return Ok(());
+        }
+        blitz.resume(now);
+        if !matches!(self.active_component, UiComponent::PracticeMain | UiComponent::PracticeControls) {
+            return Ok(());
+        }
+        if blitz.is_over(now) {
+            self.practice_skip_unanswered_question()?;
+            return self.practice_end_blitz();
+        }
+        if blitz.is_card_expired(now) {
+            return self.practice_skip_unanswered_question();
         }
         Ok(())
     }
 
+    /// Counts the current question as an error, as it was not answered in time, and moves on to the next one.
+    fn practice_skip_unanswered_question(&mut self) -> Result<(), Box<dyn Error>> {
+        let Some(question) = self.practice_get_current_question_in_round() else { return Ok(()) };
+        let expected = practice::reveal_answers(question.clone(), self.current_direction).join(" / ");
+        let result = AttemptResult::TimedOut { expected };
+        self.practice_penalize_current_question(question.clone(), result.get_penalty());
+        self.session_stats.record_attempt(&question.borrow(), false);
+        if let Some(blitz) = &mut self.blitz {
+            blitz.missed_count += 1;
+        }
+        self.answer_input.reset();
+        self.practice_set_next_question_in_round()?;
+        self.previous_attempt_result = Some(result);
+        self.practice_save_stats(question)
+    }
+
+    fn practice_end_blitz(&mut self) -> Result<(), Box<dyn Error>> {
+        let Some(blitz) = self.blitz.take() else { return Ok(()) };
+        self.open_notification_popup(format!(
+            "Blitz is over! Correct answers in {}s: {}, missed: {}",
+            BLITZ_DURATION.as_secs(),
+            blitz.correct_count,
+            blitz.missed_count
+        ).as_str())
+    }
+
+    /// Returns the global grading settings with the overrides of the question's group.
+    fn practice_get_grading_settings(&self, question: &Question) -> GradingSettings {
+        let mut settings = self.grading_settings.clone();
+        settings.language = self.config.matching.get_language(&question.group);
+        if let Some(mode) = self.questions_by_groups.get(&question.group).and_then(|group| group.diacritics_mode) {
+            settings.diacritics_mode = mode;
+        }
+        settings
+    }
+
+    fn practice_penalize_current_question(&mut self, question: Rc<RefCell<Question>>, penalty: u16) {
+        question.borrow_mut().increment_error_count(self.current_direction, penalty);
+        self.current_question_penalty = self.current_question_penalty.saturating_add(penalty);
+    }
+
+    fn practice_save_stats(&mut self, question: Rc<RefCell<Question>>) -> Result<(), Box<dyn Error>> {
+        self.stats_store.update(&question.borrow());
+        self.practice_write_stats()
+    }
+
+    fn practice_write_stats(&mut self) -> Result<(), Box<dyn Error>> {
+        match self.stats_store.save() {
+            Ok(_) => Ok(()),
+            // Saving is not critical for the practice to continue.
+            Err(e) => self.open_notification_popup(format!("Error: Cannot save practice stats: {e}").as_str()),
+        }
+    }
+
     fn practice_reset_answer(&mut self) -> Result<(), Box<dyn Error>> {
         self.answer_input.reset();
-        self.is_previous_answer_successful = None;
+        self.previous_attempt_result = None;
+        self.revealed_variants = None;
+        self.answer_diff = None;
+        self.current_question_penalty = 0;
         Ok(())
     }
 
     pub fn practice_set_next_question_in_round(&mut self) -> Result<(), Box<dyn Error>>  {
+        if self.round_mode == RoundMode::Mastery {
+            // The mastery queue decides the order, the round is never refilled.
+            return self.practice_set_next_mastery_question();
+        }
         self.current_question_index += 1;
+        self.current_question_penalty = 0;
+        self.revealed_variants = None;
+        self.answer_diff = None;
         if self.current_question_index.ge(&self.round_questions.len()) {
+            match self.round_mode {
+                RoundMode::Shuffled | RoundMode::Mastery => self.practice_shuffle_questions(),
+                RoundMode::DueToday => return self.practice_start_next_due_round(),
+                RoundMode::Leitner => return self.practice_end_leitner_session(),
+            }
+        }
+        self.practice_resolve_current_direction();
+        Ok(())
+    }
+
+    fn practice_set_next_mastery_question(&mut self) -> Result<(), Box<dyn Error>> {
+        let Some(mastery_round) = &mut self.mastery_round else { return Ok(()) };
+        // Only answers without any errors, clues or revealed answers count towards the streak.
+        mastery_round.answer_current_question(self.current_question_penalty == 0);
+        self.current_question_penalty = 0;
+        self.revealed_variants = None;
+        self.answer_diff = None;
+        if mastery_round.is_complete() {
+            let question_count = mastery_round.get_question_count();
+            self.mastery_round = None;
+            self.round_questions = self.active_questions.clone();
+            self.round_mode = RoundMode::Shuffled;
             self.practice_shuffle_questions();
+            self.practice_end_session()?;
+            if let Some(summary) = &mut self.session_summary {
+                summary.headline = Some(format!("Round complete, all {question_count} questions mastered!"));
+            }
+            return Ok(());
         }
+        self.round_questions = mastery_round.get_questions();
+        self.current_question_index = 0;
+        self.practice_resolve_current_direction();
+        Ok(())
+    }
+
+    fn practice_end_leitner_session(&mut self) -> Result<(), Box<dyn Error>> {
+        self.round_questions = self.active_questions.clone();
+        self.round_mode = RoundMode::Shuffled;
+        self.practice_shuffle_questions();
+        self.open_notification_popup("Leitner session complete! Start the next one from the menu.")
+    }
+
+    fn practice_start_next_due_round(&mut self) -> Result<(), Box<dyn Error>> {
+        // Questions that were failed are due again, the others are scheduled for later days.
+        let due_questions = practice::get_due_questions(
+            &self.round_questions,
+            practice::get_current_day(),
+            self.direction_setting.get_directions(),
+        );
+        self.current_question_index = 0;
+        if due_questions.is_empty() {
+            self.round_questions = self.active_questions.clone();
+            self.round_mode = RoundMode::Shuffled;
+            self.practice_shuffle_questions();
+            return self.open_notification_popup("All due questions are reviewed for today!");
+        }
+        self.round_questions = due_questions;
+        self.practice_resolve_current_direction();
         Ok(())
     }
 
     fn practice_shuffle_questions(&mut self) {
         self.current_question_index = 0;
         self.round_questions.shuffle(&mut rand::rng());
+        self.practice_resolve_current_direction();
     }
 
-    pub fn practice_get_current_question_in_round(&mut self) -> Rc<RefCell<Question>> {
-        self.round_questions.get(self.current_question_index).unwrap().clone()
+    pub fn practice_get_current_prompt(&mut self) -> String {
+        self.practice_get_current_question_in_round()
+            .map(|question| practice::get_prompt(&question.borrow(), self.current_direction))
+            .unwrap_or_default()
+    }
+
+    pub fn practice_get_current_question_in_round(&self) -> Option<Rc<RefCell<Question>>> {
+        self.round_questions.get(self.current_question_index).cloned()
     }
 
     pub fn practice_get_round_status_string(&mut self) -> String {
-        let feedback_message = match self.is_previous_answer_successful {
-            None => ":) Good luck!",
-            Some(true) =>  r"\o/ Yay, correct!",
-            Some(false) => r"_o_ Try again! (or request a clue form the menu)"
+        let feedback_message = match &self.previous_attempt_result {
+            None => ":) Good luck!".to_string(),
+            Some(AttemptResult::Correct) =>  r"\o/ Yay, correct!".to_string(),
+            Some(AttemptResult::AlmostCorrect { attempt, expected }) => {
+                format!("~o~ Almost! You typed '{attempt}', expected '{expected}'")
+            },
+            Some(AttemptResult::DiacriticsMismatch { expected, .. }) => {
+                format!(r"\o/ Correct, but mind the accents: '{expected}'")
+            },
+            Some(AttemptResult::Incorrect) => r"_o_ Try again! (or request a clue form the menu)".to_string(),
+            Some(AttemptResult::TimedOut { expected }) => format!("x_x Time's up! It was '{expected}'"),
         };
-        format!("{}/{} {}", self.current_question_index + 1, self.round_questions.len(), feedback_message)
+        let feedback_message = self.revealed_variants.clone().unwrap_or(feedback_message);
+        match (&self.round_mode, &self.mastery_round) {
+            (RoundMode::Mastery, Some(mastery_round)) => format!(
+                "{}/{} mastered {}",
+                mastery_round.get_mastered_count(),
+                mastery_round.get_question_count(),
+                feedback_message
+            ),
+            _ => format!("{}/{} {}", self.current_question_index + 1, self.round_questions.len(), feedback_message),
+        }
     }
 
     pub fn practice_increase_body_left_size(&mut self) -> Result<(), Box<dyn Error>> {
@@ -392,12 +1314,70 @@ impl AppState {
         Ok(())
     }
 
+    fn practice_end_session(&mut self) -> Result<(), Box<dyn Error>> {
+        self.session_summary = Some(SessionSummary::new(
+            &self.session_stats,
+            &self.active_questions,
+            self.direction_setting.get_directions(),
+        ));
+        self.session_summary_scroll = 0;
+        self.set_active_component(UiComponent::SessionSummary);
+        Ok(())
+    }
+
+    /// Starts a new session with the questions that had errors in the ended one.
+    pub fn summary_practice_missed_questions(&mut self) -> Result<(), Box<dyn Error>> {
+        let directions = self.direction_setting.get_directions();
+        let missed_questions: Vec<Rc<RefCell<Question>>> = self.active_questions.iter()
+            .filter(|question| directions.iter()
+                .any(|direction| question.borrow().get_error_count_for_round(*direction) > 0))
+            .cloned()
+            .collect();
+        if missed_questions.is_empty() {
+            return self.open_notification_popup("No missed questions to practice again.");
+        }
+        missed_questions.iter()
+            .for_each(|question| question.borrow_mut().reset_round_stats());
+        self.session_stats = SessionStats::default();
+        self.round_questions = missed_questions;
+        self.round_mode = RoundMode::Shuffled;
+        self.practice_shuffle_questions();
+        self.set_active_component(UiComponent::PracticeMain);
+        self.practice_reset_answer()
+    }
+
+    pub fn summary_export(&mut self) -> Result<(), Box<dyn Error>> {
+        let Some(summary) = &self.session_summary else { return Ok(()) };
+        let Some(dir) = SessionSummary::get_default_export_dir() else {
+            return self.open_notification_popup("Error: Cannot export summary. Data directory not found.");
+        };
+        match summary.export_to(&dir, practice::get_current_timestamp()) {
+            Ok(path) => self.open_notification_popup(format!("Summary exported to {}", path.display()).as_str()),
+            Err(e) => self.open_notification_popup(format!("Error: Cannot export summary: {e}").as_str()),
+        }
+    }
+
+    pub fn summary_scroll_up(&mut self) -> Result<(), Box<dyn Error>> {
+        self.session_summary_scroll = self.session_summary_scroll.saturating_sub(1);
+        Ok(())
+    }
+
+    pub fn summary_scroll_down(&mut self) -> Result<(), Box<dyn Error>> {
+        self.session_summary_scroll = self.session_summary_scroll.saturating_add(1);
+        Ok(())
+    }
+
+    pub fn summary_navigate_to_setup(&mut self) -> Result<(), Box<dyn Error>> {
+        self.session_summary = None;
+        self.practice_navigate_to_setup()
+    }
+
     pub fn open_notification_popup(&mut self, message: &str) -> Result<(), Box<dyn Error>> {
         self.set_active_component(UiComponent::NotificationPopup);
         self.popup_details = PopupDetails {
             title: "[ ! ]".to_string(),
-            width: message.len() as u16 + 7,
-            height: 5,
+            width: message.lines().map(|line| line.chars().count()).max().unwrap_or(0) as u16 + 7,
+            height: message.lines().count() as u16 + 4,
             message: message.to_string(),
         };
         Ok(())
@@ -407,20 +1387,190 @@ impl AppState {
         self.set_active_component(UiComponent::ExitPopup);
         self.popup_details = PopupDetails {
             title: "[ Exit ]".to_string(),
-            message: "Do you want to exit Shellingo?\n\
-                        [Enter] Yes, [Esc] No".to_string(),
+            message: format!(
+                "Do you want to exit Shellingo?\n{}",
+                self.keymap.get_help_text(&[(&[Action::Confirm], "Yes".to_string()), (&[Action::Back], "No".to_string())])
+            ),
             width: 37,
             height: 6,
         };
         Ok(())
     }
 
+    /// Stores the new problems and shows them to the user.
+    fn add_diagnostics(&mut self, diagnostics: Vec<ParseDiagnostic>) {
+        let old_count = self.diagnostics.len();
+        for diagnostic in diagnostics {
+            // The same files can be read again when a group is reactivated.
+            if !self.diagnostics.contains(&diagnostic) {
+                self.diagnostics.push(diagnostic);
+            }
+        }
+        if self.diagnostics.len() > old_count && !self.is_popup_open() {
+            self.problems_list_state.select(Some(old_count));
+            self.set_active_component(UiComponent::ProblemsPopup);
+        }
+    }
+
+    /// Shows every action of the active component, the file format and the scoring.
+    pub fn open_help_popup(&mut self) -> Result<(), Box<dyn Error>> {
+        // Only one popup is open at a time, so the help can return to the component it was opened from.
+        if self.is_popup_open() {
+            return Ok(());
+        }
+        self.help_scroll = 0;
+        self.set_active_component(UiComponent::HelpPopup);
+        Ok(())
+    }
+
+    /// Returns the component the open help popup describes.
+    pub fn get_help_component(&self) -> &UiComponent {
+        &self.last_active_component
+    }
+
+    pub fn help_scroll_up(&mut self) -> Result<(), Box<dyn Error>> {
+        self.help_scroll = self.help_scroll.saturating_sub(1);
+        Ok(())
+    }
+
+    pub fn help_scroll_down(&mut self) -> Result<(), Box<dyn Error>> {
+        self.help_scroll = self.help_scroll.saturating_add(1);
+        Ok(())
+    }
+
+    /// Focuses the component, keeping the previous one to return to if it is already focused.
+    fn focus_component(&mut self, component: UiComponent) {
+        if self.active_component != component {
+            self.set_active_component(component);
+        }
+    }
+
+    /// Selects the clicked row or practice control, toggles the group on a double click,
+    /// or starts resizing the panes when the divider between them is clicked.
+    pub fn mouse_click(&mut self, position: Position) -> Result<(), Box<dyn Error>> {
+        let is_double_click = self.click_tracker.register_click(Instant::now(), position);
+        if self.is_popup_open() || self.active_component == UiComponent::SearchInput {
+            return Ok(());
+        }
+        let areas = self.mouse_areas;
+        if mouse::is_on_divider(areas.body_left, position) {
+            self.is_dragging_divider = true;
+            return Ok(());
+        }
+        match self.get_app_phase_for_active_component() {
+            AppPhase::Setup => {
+                let group_row = mouse::get_row_index(areas.body_left, 0, self.question_group_list_state.offset(), position)
+                    .filter(|index| *index < self.group_tree_rows.len());
+                let question_row = mouse::get_row_index(areas.question_table, 1, self.question_table_state.offset(), position)
+                    .filter(|index| *index < self.setup_get_questions_for_selected_group().len());
+                if let Some(index) = group_row {
+                    self.focus_component(UiComponent::GroupSelector);
+                    self.question_group_list_state.select(Some(index));
+                    if is_double_click {
+                        return self.setup_toggle_group_active_status_and_load_questions();
+                    }
+                } else if let Some(index) = question_row {
+                    self.focus_component(UiComponent::QuestionSelector);
+                    self.question_table_state.select(Some(index));
+                }
+            },
+            AppPhase::Practice => {
+                let control_row = mouse::get_row_index(areas.body_left, 0, self.practice_controls_list_state.offset(), position)
+                    .filter(|index| *index < PracticeControlOptions::VARIANTS.len());
+                if let Some(index) = control_row {
+                    self.focus_component(UiComponent::PracticeControls);
+                    self.practice_controls_list_state.select(Some(index));
+                    return self.practice_activate_selected_control();
+                } else if areas.body_right.contains(position) {
+                    self.focus_component(UiComponent::PracticeMain);
+                }
+            },
+        }
+        Ok(())
+    }
+
+    pub fn mouse_drag(&mut self, position: Position) -> Result<(), Box<dyn Error>> {
+        if !self.is_dragging_divider {
+            return Ok(());
+        }
+        let width = mouse::get_dragged_left_pane_width(self.mouse_areas.body_left, self.mouse_areas.body_right, position.x);
+        match self.get_app_phase_for_active_component() {
+            AppPhase::Setup => self.setup_body_left_size = Some(width),
+            AppPhase::Practice => self.practice_body_left_size = Some(width),
+        }
+        Ok(())
+    }
+
+    pub fn mouse_release(&mut self) -> Result<(), Box<dyn Error>> {
+        self.is_dragging_divider = false;
+        Ok(())
+    }
+
+    /// Scrolls the list under the mouse, or the open popup.
+    pub fn mouse_scroll(&mut self, position: Position, is_down: bool) -> Result<(), Box<dyn Error>> {
+        let areas = self.mouse_areas;
+        match (&self.active_component, is_down) {
+            (UiComponent::HelpPopup, false) => self.help_scroll_up(),
+            (UiComponent::HelpPopup, true) => self.help_scroll_down(),
+            (UiComponent::SessionSummary, false) => self.summary_scroll_up(),
+            (UiComponent::SessionSummary, true) => self.summary_scroll_down(),
+            (UiComponent::ProblemsPopup, false) => self.problems_select_previous(),
+            (UiComponent::ProblemsPopup, true) => self.problems_select_next(),
+            (UiComponent::GroupSelector | UiComponent::QuestionSelector, _) if areas.body_left.contains(position) => {
+                if is_down { self.setup_next_group() } else { self.setup_previous_group() }
+            },
+            (UiComponent::GroupSelector | UiComponent::QuestionSelector, _) if areas.question_table.contains(position) => {
+                if is_down { self.setup_next_question() } else { self.setup_previous_question() }
+            },
+            (UiComponent::PracticeControls | UiComponent::PracticeMain, _) if areas.body_left.contains(position) => {
+                if is_down { self.practice_select_next_menu_item() } else { self.practice_select_previous_menu_item() }
+            },
+            _ => Ok(()),
+        }
+    }
+
+    pub fn open_problems_popup(&mut self) -> Result<(), Box<dyn Error>> {
+        if self.diagnostics.is_empty() {
+            return self.open_notification_popup("No problems found in the question files.");
+        }
+        if self.problems_list_state.selected().is_none() {
+            self.problems_list_state.select_first();
+        }
+        self.set_active_component(UiComponent::ProblemsPopup);
+        Ok(())
+    }
+
+    pub fn problems_select_previous(&mut self) -> Result<(), Box<dyn Error>> {
+        self.problems_list_state.select_previous();
+        Ok(())
+    }
+
+    pub fn problems_select_next(&mut self) -> Result<(), Box<dyn Error>> {
+        self.problems_list_state.select_next();
+        Ok(())
+    }
+
+    fn is_popup_open(&self) -> bool {
+        matches!(
+            self.active_component,
+            UiComponent::ExitPopup
+                | UiComponent::NotificationPopup
+                | UiComponent::ProblemsPopup
+                | UiComponent::SessionSummary
+                | UiComponent::DeckNameInput
+                | UiComponent::HelpPopup
+        )
+    }
+
     pub fn close_popup(&mut self) -> Result<(), Box<dyn Error>> {
         self.set_active_component(self.last_active_component.clone());
         Ok(())
     }
 
     pub fn exit_app(&mut self) -> Result<(), Box<dyn Error>> {
+        if let Err(e) = self.save_session_state() {
+            return Err(Box::from(format!("Exiting application. Cannot save the selected groups and options: {e}")));
+        }
         Err(Box::from("Exiting application."))
     }
 
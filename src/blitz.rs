@@ -0,0 +1,124 @@
+use std::time::{Duration, Instant};
+
+pub const BLITZ_DURATION: Duration = Duration::from_secs(60);
+pub const BLITZ_CARD_TIME_LIMIT: Duration = Duration::from_secs(10);
+
+/// A timed practice, scored by the number of correct answers within the time budget.
+/// Cards that are not answered within their own time limit are counted as errors.
+#[derive(Debug, Clone)]
+pub struct Blitz {
+    started_at: Instant,
+    card_started_at: Instant,
+    /// Set while the clocks are stopped, e.g. while a popup covers the practice.
+    paused_at: Option<Instant>,
+    pub correct_count: u32,
+    pub missed_count: u32,
+}
+
+impl Blitz {
+    pub fn start(now: Instant) -> Self {
+        Self {
+            started_at: now,
+            card_started_at: now,
+            paused_at: None,
+            correct_count: 0,
+            missed_count: 0,
+        }
+    }
+
+    pub fn start_card(&mut self, now: Instant) {
+        self.card_started_at = now;
+    }
+
+    /// Stops the clocks of the blitz and the current card until resumed.
+    pub fn pause(&mut self, now: Instant) {
+        self.paused_at.get_or_insert(now);
+    }
+
+    /// Restarts the clocks, moving their start times by the paused duration.
+    pub fn resume(&mut self, now: Instant) {
+        let Some(paused_at) = self.paused_at.take() else { return };
+        let paused_duration = now.saturating_duration_since(paused_at);
+        self.started_at += paused_duration;
+        self.card_started_at += paused_duration;
+    }
+
+    pub fn get_remaining_time(&self, now: Instant) -> Duration {
+        let now = self.paused_at.unwrap_or(now);
+        BLITZ_DURATION.saturating_sub(now.saturating_duration_since(self.started_at))
+    }
+
+    /// Returns the time left for the current card, never more than the time left for the whole blitz.
+    pub fn get_remaining_card_time(&self, now: Instant) -> Duration {
+        let now = self.paused_at.unwrap_or(now);
+        BLITZ_CARD_TIME_LIMIT.saturating_sub(now.saturating_duration_since(self.card_started_at))
+            .min(self.get_remaining_time(now))
+    }
+
+    pub fn is_over(&self, now: Instant) -> bool {
+        self.get_remaining_time(now).is_zero()
+    }
+
+    pub fn is_card_expired(&self, now: Instant) -> bool {
+        self.get_remaining_card_time(now).is_zero()
+    }
+
+    /// Returns the ratio of the remaining time, from 1.0 at the start to 0.0 at the end.
+    pub fn get_remaining_ratio(&self, now: Instant) -> f64 {
+        self.get_remaining_time(now).as_secs_f64() / BLITZ_DURATION.as_secs_f64()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn card_expires_before_the_blitz_is_over() {
+        // Given
+        let start = Instant::now();
+        let mut blitz = Blitz::start(start);
+
+        // When
+        let after_card_limit = start + BLITZ_CARD_TIME_LIMIT;
+
+        // Then
+        assert!(blitz.is_card_expired(after_card_limit));
+        assert!(!blitz.is_over(after_card_limit));
+        blitz.start_card(after_card_limit);
+        assert!(!blitz.is_card_expired(after_card_limit));
+    }
+
+    #[test]
+    fn paused_time_is_not_counted() {
+        // Given
+        let start = Instant::now();
+        let mut blitz = Blitz::start(start);
+        blitz.pause(start + Duration::from_secs(2));
+
+        // When
+        let remaining_while_paused = blitz.get_remaining_card_time(start + Duration::from_secs(30));
+        blitz.resume(start + Duration::from_secs(32));
+
+        // Then
+        assert_eq!(remaining_while_paused, BLITZ_CARD_TIME_LIMIT - Duration::from_secs(2));
+        assert_eq!(blitz.get_remaining_card_time(start + Duration::from_secs(33)), BLITZ_CARD_TIME_LIMIT - Duration::from_secs(3));
+        assert_eq!(blitz.get_remaining_time(start + Duration::from_secs(33)), BLITZ_DURATION - Duration::from_secs(3));
+    }
+
+    #[test]
+    fn card_time_is_limited_by_the_remaining_blitz_time() {
+        // Given
+        let start = Instant::now();
+        let mut blitz = Blitz::start(start);
+        let near_the_end = start + BLITZ_DURATION - Duration::from_secs(3);
+
+        // When
+        blitz.start_card(near_the_end);
+
+        // Then
+        assert_eq!(blitz.get_remaining_card_time(near_the_end), Duration::from_secs(3));
+        assert!(blitz.is_over(start + BLITZ_DURATION));
+        assert_eq!(blitz.get_remaining_ratio(start + BLITZ_DURATION * 2), 0.0);
+    }
+}
@@ -0,0 +1,375 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use toml::{Table, Value};
+use crate::keymap::{Keymap, KeysConfig};
+use crate::group_tree;
+use crate::practice::{IgnoredSymbols, Language, RevealPenalties, DEFAULT_IGNORED_SYMBOLS, DEFAULT_TYPO_TOLERANCE};
+use crate::json_store::APP_DIR_NAME;
+use crate::theme::ThemeName;
+
+const CONFIG_FILE_NAME: &str = "config.toml";
+/// The config file read from the input paths, to share settings together with the vocabulary files.
+pub const REPO_CONFIG_FILE_NAME: &str = ".shellingo.toml";
+/// The commandline flag overriding a single setting, e.g. `--set penalties.clue_reveal=3`
+pub const CONFIG_OVERRIDE_FLAG: &str = "--set";
+/// The commandline flag picking the theme, a shorthand for `--set appearance.theme="<name>"`
+pub const THEME_FLAG: &str = "--theme";
+const MIN_PANE_WIDTH: u16 = 10;
+const MAX_POLL_INTERVAL_MS: u64 = 1000;
+
+/// The settings of the app, merged from the built-in defaults, the user config file,
+/// the `.shellingo.toml` files of the input paths and the commandline overrides, in this order.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub matching: MatchingConfig,
+    pub penalties: RevealPenalties,
+    pub layout: LayoutConfig,
+    pub input: InputConfig,
+    pub keys: KeysConfig,
+    pub appearance: AppearanceConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MatchingConfig {
+    /// The characters removed from the attempts and answers before comparing them.
+    pub ignored_symbols: String,
+    /// The number of typos accepted as "almost correct", relative to the length of the answer.
+    pub typo_tolerance: f32,
+    /// The language of the special letters folded when the accents are ignored, `any` folds all of them.
+    pub language: Language,
+    /// The languages of single groups or folders of groups, overriding `language`.
+    pub group_languages: BTreeMap<String, Language>,
+}
+
+impl Default for MatchingConfig {
+    fn default() -> Self {
+        MatchingConfig {
+            ignored_symbols: DEFAULT_IGNORED_SYMBOLS.to_string(),
+            typo_tolerance: DEFAULT_TYPO_TOLERANCE,
+            language: Language::default(),
+            group_languages: BTreeMap::new(),
+        }
+    }
+}
+
+impl MatchingConfig {
+    /// Returns the language set for the group, or for its closest folder.
+    pub fn get_language(&self, group_name: &str) -> Language {
+        self.group_languages.iter()
+            .filter(|(path, _)| group_name == path.as_str() || group_tree::is_in_folder(group_name, path))
+            .max_by_key(|(path, _)| path.len())
+            .map_or(self.language, |(_, language)| *language)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LayoutConfig {
+    /// The initial width of the group list in the setup phase.
+    pub setup_left_pane_width: u16,
+    /// The initial width of the controls in the practice phase.
+    pub practice_left_pane_width: u16,
+}
+
+impl Default for LayoutConfig {
+    fn default() -> Self {
+        LayoutConfig {
+            setup_left_pane_width: 45,
+            practice_left_pane_width: 22,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct InputConfig {
+    /// How long to wait for a key press before redrawing, e.g. to update the blitz timer.
+    pub poll_interval_ms: u64,
+    /// Clicks, scrolling and dragging in the app. Disable to select text with the mouse instead.
+    pub mouse_capture: bool,
+}
+
+impl Default for InputConfig {
+    fn default() -> Self {
+        InputConfig { poll_interval_ms: 100, mouse_capture: true }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AppearanceConfig {
+    /// The named palette of the UI, the default one or the monochrome one with `NO_COLOR` if not set.
+    pub theme: Option<ThemeName>,
+}
+
+impl Config {
+    /// Returns every invalid value of the config.
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = vec![];
+        if let Some(symbol) = self.matching.ignored_symbols.chars().find(|c| c.is_alphanumeric() || c.is_whitespace()) {
+            problems.push(format!("matching.ignored_symbols cannot contain letters, digits or spaces, found '{symbol}'"));
+        } else if let Err(e) = IgnoredSymbols::new(&self.matching.ignored_symbols) {
+            problems.push(format!("matching.ignored_symbols: {e}"));
+        }
+        if !(0.0..=1.0).contains(&self.matching.typo_tolerance) {
+            problems.push("matching.typo_tolerance must be between 0 and 1".to_string());
+        }
+        // A single typo counts as a better recall than a revealed clue in the spaced repetition.
+        if self.penalties.clue_reveal < 2 {
+            problems.push("penalties.clue_reveal must be at least 2".to_string());
+        }
+        if self.penalties.answer_reveal < self.penalties.clue_reveal {
+            problems.push("penalties.answer_reveal cannot be lower than penalties.clue_reveal".to_string());
+        }
+        if self.layout.setup_left_pane_width < MIN_PANE_WIDTH || self.layout.practice_left_pane_width < MIN_PANE_WIDTH {
+            problems.push(format!("layout pane widths must be at least {MIN_PANE_WIDTH}"));
+        }
+        if !(1..=MAX_POLL_INTERVAL_MS).contains(&self.input.poll_interval_ms) {
+            problems.push(format!("input.poll_interval_ms must be between 1 and {MAX_POLL_INTERVAL_MS}"));
+        }
+        if let Err(e) = Keymap::new(&self.keys) {
+            problems.push(format!("keys.bindings: {e}"));
+        }
+        problems
+    }
+}
+
+/// A source of settings, with its TOML contents.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigLayer {
+    /// The file path or commandline flag the settings come from, shown in the errors.
+    pub source: String,
+    pub contents: String,
+}
+
+/// Returns the location of the user config file (e.g. `$XDG_CONFIG_HOME/shellingo/config.toml`)
+pub fn get_user_config_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join(APP_DIR_NAME).join(CONFIG_FILE_NAME))
+}
+
+/// Returns the config overrides given on the commandline and the remaining arguments.
+pub fn get_config_overrides_from(args: Vec<String>) -> (Vec<String>, Vec<String>) {
+    let mut overrides = vec![];
+    let mut other_args = vec![];
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == CONFIG_OVERRIDE_FLAG {
+            overrides.extend(args.next());
+        } else if arg == THEME_FLAG {
+            overrides.extend(args.next().map(|name| get_theme_override(&name)));
+        } else if let Some(name) = arg.strip_prefix(&format!("{THEME_FLAG}=")) {
+            overrides.push(get_theme_override(name));
+        } else if let Some(setting) = arg.strip_prefix(&format!("{CONFIG_OVERRIDE_FLAG}=")) {
+            overrides.push(setting.to_string());
+        } else {
+            other_args.push(arg);
+        }
+    }
+    (overrides, other_args)
+}
+
+fn get_theme_override(name: &str) -> String {
+    format!("appearance.theme={}", Value::String(name.to_string()))
+}
+
+/// Reads the user config file, the `.shellingo.toml` files of the input paths and the commandline overrides,
+/// in the order they are applied.
+pub fn get_config_layers(input_paths: &[PathBuf], overrides: &[String]) -> (Vec<ConfigLayer>, Vec<String>) {
+    let mut config_paths: Vec<PathBuf> = get_user_config_path().into_iter().collect();
+    for input_path in input_paths {
+        let dir = if input_path.is_dir() { Some(input_path.as_path()) } else { input_path.parent() };
+        if let Some(repo_config_path) = dir.map(|dir| dir.join(REPO_CONFIG_FILE_NAME))
+            && !config_paths.contains(&repo_config_path) {
+            config_paths.push(repo_config_path);
+        }
+    }
+
+    let mut layers = vec![];
+    let mut errors = vec![];
+    for path in config_paths.iter().filter(|path| path.exists()) {
+        match fs::read_to_string(path) {
+            Ok(contents) => layers.push(ConfigLayer { source: path.display().to_string(), contents }),
+            Err(e) => errors.push(format!("{}: {}", path.display(), e)),
+        }
+    }
+    layers.extend(overrides.iter().map(|setting| ConfigLayer {
+        source: format!("{CONFIG_OVERRIDE_FLAG} {setting}"),
+        contents: setting.clone(),
+    }));
+    (layers, errors)
+}
+
+/// Applies the layers on top of the defaults. A layer that cannot be parsed or results in an invalid config
+/// is skipped as a whole, and its problems are returned.
+pub fn merge_layers(layers: &[ConfigLayer]) -> (Config, Vec<String>) {
+    let mut merged = Table::new();
+    let mut config = Config::default();
+    let mut errors = vec![];
+    for layer in layers {
+        let table = match layer.contents.parse::<Table>() {
+            Ok(table) => table,
+            Err(e) => {
+                errors.push(format!("{}: {}", layer.source, e.message()));
+                continue;
+            }
+        };
+        let mut candidate = merged.clone();
+        merge_tables(&mut candidate, table);
+        match Value::Table(candidate.clone()).try_into::<Config>() {
+            Ok(candidate_config) => {
+                let problems = candidate_config.validate();
+                if problems.is_empty() {
+                    merged = candidate;
+                    config = candidate_config;
+                } else {
+                    errors.extend(problems.into_iter().map(|problem| format!("{}: {}", layer.source, problem)));
+                }
+            }
+            Err(e) => errors.push(format!("{}: {}", layer.source, e.message())),
+        }
+    }
+    (config, errors)
+}
+
+/// Loads the config for the input paths, returning the problems of the skipped layers.
+pub fn load_config(input_paths: &[PathBuf], overrides: &[String]) -> (Config, Vec<String>) {
+    let (layers, mut errors) = get_config_layers(input_paths, overrides);
+    let (config, merge_errors) = merge_layers(&layers);
+    errors.extend(merge_errors);
+    (config, errors)
+}
+
+/// Copies the values of the overlay into the base, merging the nested tables key by key.
+fn merge_tables(base: &mut Table, overlay: Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(Value::Table(base_table)), Value::Table(overlay_table)) => merge_tables(base_table, overlay_table),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keymap::KeymapPreset;
+
+    fn get_layer(source: &str, contents: &str) -> ConfigLayer {
+        ConfigLayer { source: source.to_string(), contents: contents.to_string() }
+    }
+
+    #[test]
+    fn later_layers_override_single_values_of_earlier_layers() {
+        // Given
+        let layers = [
+            get_layer("user", "[penalties]\nclue_reveal = 3\nanswer_reveal = 6\n[layout]\nsetup_left_pane_width = 60"),
+            get_layer("repo", "[penalties]\nanswer_reveal = 8\n[matching]\nignored_symbols = \"?!\""),
+            get_layer("--set", "layout.practice_left_pane_width = 30"),
+            get_layer("--set", "keys.preset = \"vim\""),
+        ];
+
+        // When
+        let (config, errors) = merge_layers(&layers);
+
+        // Then
+        assert_eq!(errors, Vec::<String>::new());
+        assert_eq!(config.penalties, RevealPenalties { clue_reveal: 3, answer_reveal: 8 });
+        assert_eq!(config.matching.ignored_symbols, "?!");
+        assert_eq!((config.layout.setup_left_pane_width, config.layout.practice_left_pane_width), (60, 30));
+        assert_eq!(config.input, InputConfig::default());
+        assert_eq!(config.keys.preset, KeymapPreset::Vim);
+    }
+
+    #[test]
+    fn invalid_layers_are_skipped_with_their_problems() {
+        // Given
+        let layers = [
+            get_layer("user", "[input]\npoll_interval_ms = 50"),
+            get_layer("repo", "[penalties]\nclue_reveal = 20"),
+            get_layer("typo", "[layuot]\nsetup_left_pane_width = 60"),
+            get_layer("broken", "[matching"),
+            get_layer("keys", "[keys.bindings]\nstart_practice = [\"Hyper+p\"]"),
+        ];
+
+        // When
+        let (config, errors) = merge_layers(&layers);
+
+        // Then
+        assert_eq!(config, Config { input: InputConfig { poll_interval_ms: 50, ..InputConfig::default() }, ..Config::default() });
+        assert_eq!(errors.len(), 4);
+        assert_eq!(errors[0], "repo: penalties.answer_reveal cannot be lower than penalties.clue_reveal");
+        assert!(errors[1].starts_with("typo: unknown field `layuot`"));
+        assert!(errors[2].starts_with("broken: "));
+        assert_eq!(errors[3], "keys: keys.bindings: unknown modifier 'Hyper' in key 'Hyper+p'");
+    }
+
+    #[test]
+    fn clue_reveal_penalty_must_be_higher_than_a_typo() {
+        // Given
+        let layers = [get_layer("user", "[penalties]\nclue_reveal = 1"), get_layer("repo", "[penalties]\nclue_reveal = 2")];
+
+        // When
+        let (config, errors) = merge_layers(&layers);
+
+        // Then
+        assert_eq!(errors, vec!["user: penalties.clue_reveal must be at least 2".to_string()]);
+        assert_eq!(config.penalties.clue_reveal, 2);
+    }
+
+    #[test]
+    fn group_language_is_taken_from_the_closest_folder() {
+        // Given
+        let layers = [get_layer("user", "[matching]\nlanguage = \"german\"\n[matching.group_languages]\npolish = \"polish\"\n\"polish/croatian\" = \"croatian\"")];
+
+        // When
+        let (config, errors) = merge_layers(&layers);
+
+        // Then
+        assert_eq!(errors, Vec::<String>::new());
+        assert_eq!(config.matching.get_language("polish/lesson1/numbers"), Language::Polish);
+        assert_eq!(config.matching.get_language("polish/croatian/numbers"), Language::Croatian);
+        assert_eq!(config.matching.get_language("polish"), Language::Polish);
+        assert_eq!(config.matching.get_language("polishing"), Language::German);
+    }
+
+    #[test]
+    fn repo_config_is_read_next_to_the_input_files() {
+        // Given
+        let input_paths = [PathBuf::from("tests/fixtures/config"), PathBuf::from("tests/fixtures/config/numbers.sll")];
+
+        // When
+        let (layers, errors) = get_config_layers(&input_paths, &["input.poll_interval_ms=50".to_string()]);
+        let repo_layers: Vec<&ConfigLayer> = layers.iter()
+            .filter(|layer| layer.source.ends_with(REPO_CONFIG_FILE_NAME))
+            .collect();
+
+        // Then
+        assert_eq!(errors, Vec::<String>::new());
+        assert_eq!(repo_layers.len(), 1);
+        assert!(repo_layers[0].contents.contains("clue_reveal = 3"));
+        assert_eq!(layers.last().unwrap().source, "--set input.poll_interval_ms=50");
+    }
+
+    #[test]
+    fn overrides_are_taken_from_the_arguments() {
+        // Given
+        let args = ["polish", "--set", "penalties.clue_reveal=3", "--set=input.poll_interval_ms=50", "german", "--theme", "monochrome"]
+            .map(String::from)
+            .to_vec();
+
+        // When
+        let (overrides, other_args) = get_config_overrides_from(args);
+
+        // Then
+        assert_eq!(overrides, vec!["penalties.clue_reveal=3", "input.poll_interval_ms=50", "appearance.theme=\"monochrome\""]);
+        assert_eq!(other_args, vec!["polish", "german"]);
+        let theme_layer = get_config_layers(&[], &overrides[2..]).0;
+        assert_eq!(merge_layers(&theme_layer).0.appearance.theme, Some(ThemeName::Monochrome));
+    }
+}
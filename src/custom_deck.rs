@@ -0,0 +1,76 @@
+use std::collections::{BTreeMap, BTreeSet};
+use serde::{Deserialize, Serialize};
+use crate::question::Question;
+use crate::json_store::JsonStore;
+
+pub const DECKS_FILE_NAME: &str = "decks.json";
+
+/// A question picked for a custom deck, referenced by its group and question text.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct DeckEntry {
+    /// The identity of the group, or its name in the decks saved by earlier versions.
+    pub group: String,
+    pub question: String,
+}
+
+impl DeckEntry {
+    pub fn from_question(question: &Question) -> Self {
+        Self {
+            group: question.group_id.clone(),
+            question: question.question.clone(),
+        }
+    }
+}
+
+/// Named sets of hand-picked questions, shown in the group list next to the groups read from files.
+pub type CustomDeckStore = JsonStore<BTreeMap<String, BTreeSet<DeckEntry>>>;
+
+impl CustomDeckStore {
+    pub fn get_decks(&self) -> &BTreeMap<String, BTreeSet<DeckEntry>> {
+        self.get_data()
+    }
+
+    /// Adds a deck, replacing the deck with the same name if there is one.
+    pub fn insert(&mut self, name: &str, entries: BTreeSet<DeckEntry>) {
+        self.get_data_mut().insert(name.to_string(), entries);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json_store::get_temp_store_path;
+
+    fn get_entry(group: &str, question: &str) -> DeckEntry {
+        DeckEntry { group: group.to_string(), question: question.to_string() }
+    }
+
+    #[test]
+    fn decks_are_restored_after_saving() {
+        // Given
+        let path = get_temp_store_path("decks_are_restored_after_saving", DECKS_FILE_NAME);
+        let mut store = CustomDeckStore::load_from(path.clone()).unwrap();
+        let entries = BTreeSet::from([get_entry("animals", "dog"), get_entry("numbers", "one")]);
+
+        // When
+        store.insert("favourites", entries.clone());
+        store.save().unwrap();
+        let restored = CustomDeckStore::load_from(path).unwrap();
+
+        // Then
+        assert_eq!(restored.get_decks(), &BTreeMap::from([("favourites".to_string(), entries)]));
+    }
+
+    #[test]
+    fn deck_with_the_same_name_is_replaced() {
+        // Given
+        let mut store = CustomDeckStore::in_memory();
+        store.insert("favourites", BTreeSet::from([get_entry("animals", "dog")]));
+
+        // When
+        store.insert("favourites", BTreeSet::from([get_entry("animals", "cat")]));
+
+        // Then
+        assert_eq!(store.get_decks()["favourites"], BTreeSet::from([get_entry("animals", "cat")]));
+    }
+}
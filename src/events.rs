@@ -1,13 +1,16 @@
 use crate::app::{AppState, UiComponent};
+use crate::keymap::{get_component_actions, Action};
+use crate::practice::AnswerMode;
 use ratatui::crossterm::event;
-use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::crossterm::event::{KeyEvent, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::Position;
 use ratatui::crossterm::event::{Event};
 use std::error::Error;
 use ratatui::crossterm;
 use tui_input::backend::crossterm::EventHandler;
 
 pub fn handle_input(app: &mut AppState) -> Result<(), Box<dyn Error>> {
-    if event::poll(std::time::Duration::from_millis(100))? {
+    if event::poll(std::time::Duration::from_millis(app.config.input.poll_interval_ms))? {
         let input_event = crossterm::event::read()?;
         match input_event {
             Event::Key(key) => {
@@ -15,101 +18,204 @@ pub fn handle_input(app: &mut AppState) -> Result<(), Box<dyn Error>> {
                     // Setup phase
                     UiComponent::GroupSelector => handle_setup_group_selector_input(app, key),
                     UiComponent::QuestionSelector => handle_setup_question_selector_input(app, key),
+                    UiComponent::SearchInput => handle_search_input(app, input_event),
+                    UiComponent::DeckNameInput => handle_deck_name_input(app, input_event),
 
                     // Practice phase
                     UiComponent::PracticeControls => handle_practice_controls_input(app, key),
                     UiComponent::PracticeMain => handle_practice_main_input(app, input_event),
+                    UiComponent::SessionSummary => handle_session_summary_input(app, key),
 
                     // Exit
                     UiComponent::ExitPopup => handle_exit_popup_input(app, key),
                     UiComponent::NotificationPopup => handle_notification_popup_input(app, key),
+                    UiComponent::ProblemsPopup => handle_problems_popup_input(app, key),
+                    UiComponent::HelpPopup => handle_help_popup_input(app, key),
                 }
             }
 
-            // FIXME: Mouse events are not reported.
-            // Event::Mouse(MouseEvent { kind, column, row, modifiers }) => {
-            //     println!("{:?}, {:?}, {:?}, {:?}", column, row, kind, column);
-            //     Ok(())
-            // },
+            Event::Mouse(mouse) => handle_mouse_input(app, mouse),
 
             _ => Ok(())
-        }
-    } else {
-        Ok(())
+        }?;
+    }
+    // Called after every poll timeout too, so the timers run without any input.
+    app.practice_tick()
+}
+
+fn handle_mouse_input(app: &mut AppState, mouse: MouseEvent) -> Result<(), Box<dyn Error>> {
+    let position = Position::new(mouse.column, mouse.row);
+    match mouse.kind {
+        MouseEventKind::Down(MouseButton::Left) => app.mouse_click(position),
+        MouseEventKind::Drag(MouseButton::Left) => app.mouse_drag(position),
+        MouseEventKind::Up(MouseButton::Left) => app.mouse_release(),
+        MouseEventKind::ScrollUp => app.mouse_scroll(position, false),
+        MouseEventKind::ScrollDown => app.mouse_scroll(position, true),
+        _ => Ok(()),
     }
 }
 
 fn handle_setup_group_selector_input(app: &mut AppState, key: KeyEvent) -> Result<(), Box<dyn Error>> {
-    match key.code {
-        KeyCode::Up | KeyCode::Char('k') => app.setup_previous_group(),
-        KeyCode::Down | KeyCode::Char('j') => app.setup_next_group(),
-        KeyCode::Enter | KeyCode::Char(' ') => app.setup_toggle_group_active_status_and_load_questions(),
-        KeyCode::Char('p') => app.setup_navigate_to_practice(),
-        KeyCode::Char('+') => app.setup_increase_body_left_size(),
-        KeyCode::Char('-') => app.setup_decrease_body_left_size(),
-        KeyCode::Char('#') => app.setup_reset_body_left_size(),
-        KeyCode::Tab | KeyCode::Left | KeyCode::Right => app.setup_toggle_panes(),
-        KeyCode::Esc => app.open_exit_popup(),
+    match app.keymap.get_action(&key, get_component_actions(&UiComponent::GroupSelector)) {
+        Some(Action::MoveUp) => app.setup_previous_group(),
+        Some(Action::MoveDown) => app.setup_next_group(),
+        Some(Action::Select) => app.setup_toggle_group_active_status_and_load_questions(),
+        Some(Action::StartPractice) => app.setup_navigate_to_practice(),
+        Some(Action::ToggleDirection) => app.setup_toggle_direction(),
+        Some(Action::ToggleAnswerMode) => app.toggle_answer_mode(),
+        Some(Action::ToggleGroupAccents) => app.setup_toggle_group_diacritics_mode(),
+        Some(Action::ToggleAccents) => app.toggle_diacritics_mode(),
+        Some(Action::Search) => app.setup_open_search(),
+        Some(Action::NextSearchHit) => app.setup_jump_to_search_hit(true),
+        Some(Action::PreviousSearchHit) => app.setup_jump_to_search_hit(false),
+        Some(Action::SelectSearchHits) => app.setup_activate_shown_groups(),
+        Some(Action::SaveDeck) => app.setup_open_deck_name_input(),
+        Some(Action::ShowProblems) => app.open_problems_popup(),
+        // The group list is the left pane.
+        Some(Action::GrowPane) => app.setup_increase_body_left_size(),
+        Some(Action::ShrinkPane) => app.setup_decrease_body_left_size(),
+        Some(Action::ResetPaneSize) => app.setup_reset_body_left_size(),
+        Some(Action::MoveLeft) => app.setup_collapse_selected_folder(),
+        Some(Action::MoveRight) => app.setup_expand_selected_folder(),
+        Some(Action::SwitchPanes) => app.setup_toggle_panes(),
+        Some(Action::ShowHelp) => app.open_help_popup(),
+        Some(Action::Back) => app.open_exit_popup(),
         _ => Ok(()),
     }
 }
 
 fn handle_setup_question_selector_input(app: &mut AppState, key: KeyEvent) -> Result<(), Box<dyn Error>> {
-    match key.code {
-        KeyCode::Up | KeyCode::Char('k') => app.setup_previous_question(),
-        KeyCode::Down | KeyCode::Char('j') => app.setup_next_question(),
-        KeyCode::Char('p') => app.setup_navigate_to_practice(),
-        KeyCode::Char('-') => app.setup_increase_body_left_size(),
-        KeyCode::Char('+') => app.setup_decrease_body_left_size(),
-        KeyCode::Char('#') => app.setup_reset_body_left_size(),
-        KeyCode::Tab | KeyCode::Left | KeyCode::Right => app.setup_toggle_panes(),
-        KeyCode::Esc => app.open_exit_popup(),
+    match app.keymap.get_action(&key, get_component_actions(&UiComponent::QuestionSelector)) {
+        Some(Action::MoveUp) => app.setup_previous_question(),
+        Some(Action::MoveDown) => app.setup_next_question(),
+        Some(Action::Select) => app.setup_toggle_selected_question_mark(),
+        Some(Action::InvertMarks) => app.setup_invert_question_marks(),
+        Some(Action::SaveDeck) => app.setup_open_deck_name_input(),
+        Some(Action::StartPractice) => app.setup_navigate_to_practice(),
+        Some(Action::ToggleDirection) => app.setup_toggle_direction(),
+        Some(Action::ToggleAnswerMode) => app.toggle_answer_mode(),
+        Some(Action::Search) => app.setup_open_search(),
+        Some(Action::NextSearchHit) => app.setup_jump_to_search_hit(true),
+        Some(Action::PreviousSearchHit) => app.setup_jump_to_search_hit(false),
+        Some(Action::SelectSearchHits) => app.setup_activate_shown_groups(),
+        Some(Action::ShowProblems) => app.open_problems_popup(),
+        // The question table is the right pane.
+        Some(Action::GrowPane) => app.setup_decrease_body_left_size(),
+        Some(Action::ShrinkPane) => app.setup_increase_body_left_size(),
+        Some(Action::ResetPaneSize) => app.setup_reset_body_left_size(),
+        Some(Action::SwitchPanes | Action::MoveLeft | Action::MoveRight) => app.setup_toggle_panes(),
+        Some(Action::ShowHelp) => app.open_help_popup(),
+        Some(Action::Back) => app.open_exit_popup(),
         _ => Ok(()),
     }
 }
 
+fn handle_search_input(app: &mut AppState, event: Event) -> Result<(), Box<dyn Error>> {
+    let key_event = event.as_key_event().expect("Event expected to be a key event at this point");
+    match app.keymap.get_text_input_action(&key_event, get_component_actions(&UiComponent::SearchInput)) {
+        Some(Action::Confirm) => app.search_confirm(),
+        Some(Action::Back) => app.search_cancel(),
+        Some(Action::ToggleSearchMode) => app.search_toggle_mode(),
+        _ => app.search_handle_input(&event),
+    }
+}
+
+fn handle_deck_name_input(app: &mut AppState, event: Event) -> Result<(), Box<dyn Error>> {
+    let key_event = event.as_key_event().expect("Event expected to be a key event at this point");
+    match app.keymap.get_text_input_action(&key_event, get_component_actions(&UiComponent::DeckNameInput)) {
+        Some(Action::Confirm) => app.deck_name_confirm(),
+        Some(Action::Back) => app.deck_name_cancel(),
+        _ => app.deck_name_handle_input(&event),
+    }
+}
+
 fn handle_practice_controls_input(app: &mut AppState, key: KeyEvent) -> Result<(), Box<dyn Error>> {
-    match key.code {
-        KeyCode::Up | KeyCode::Char('k') => app.practice_select_previous_menu_item(),
-        KeyCode::Down | KeyCode::Char('j') => app.practice_select_next_menu_item(),
-        KeyCode::Enter => app.practice_activate_selected_control(),
-        KeyCode::Tab | KeyCode::Left | KeyCode::Right => app.practice_toggle_panes(),
-        KeyCode::Char('+') => app.practice_increase_body_left_size(),
-        KeyCode::Char('-') => app.practice_decrease_body_left_size(),
-        KeyCode::Char('#') => app.practice_reset_body_left_size(),
-        KeyCode::Esc => app.open_exit_popup(),
+    match app.keymap.get_action(&key, get_component_actions(&UiComponent::PracticeControls)) {
+        Some(Action::MoveUp) => app.practice_select_previous_menu_item(),
+        Some(Action::MoveDown) => app.practice_select_next_menu_item(),
+        Some(Action::Select) => app.practice_activate_selected_control(),
+        Some(Action::SwitchPanes | Action::MoveLeft | Action::MoveRight) => app.practice_toggle_panes(),
+        // The controls are the left pane.
+        Some(Action::GrowPane) => app.practice_increase_body_left_size(),
+        Some(Action::ShrinkPane) => app.practice_decrease_body_left_size(),
+        Some(Action::ResetPaneSize) => app.practice_reset_body_left_size(),
+        Some(Action::ShowHelp) => app.open_help_popup(),
+        Some(Action::Back) => app.open_exit_popup(),
         _ => Ok(()),
     }
 }
 
 fn handle_practice_main_input(app: &mut AppState, event: Event) -> Result<(), Box<dyn Error>> {
-    let key_event = event.as_key_event();
-    match key_event.expect("Event expected to be a key event at this point").code { 
-        KeyCode::Tab => app.practice_toggle_panes(),
-        KeyCode::Enter => app.practice_validate_attempt(),
-        KeyCode::Esc => app.open_exit_popup(),
-        KeyCode::Char('-') => app.practice_increase_body_left_size(),
-        KeyCode::Char('+') => app.practice_decrease_body_left_size(),
-        KeyCode::Char('#') => app.practice_reset_body_left_size(),
+    let key_event = event.as_key_event().expect("Event expected to be a key event at this point");
+    let actions = get_component_actions(&UiComponent::PracticeMain);
+    // Every character is typed into the answer, unless the answers are picked from the options.
+    let action = match app.answer_mode {
+        AnswerMode::Typing => app.keymap.get_text_input_action(&key_event, actions),
+        AnswerMode::MultipleChoice => app.keymap.get_action(&key_event, actions),
+    };
+    match action {
+        Some(Action::SwitchPanes) => app.practice_toggle_panes(),
+        Some(Action::Confirm) if app.answer_mode == AnswerMode::MultipleChoice => Ok(()),
+        Some(Action::Confirm) => app.practice_validate_attempt(),
+        Some(Action::PickOption) if app.answer_mode == AnswerMode::MultipleChoice => {
+            match app.keymap.get_binding_index(Action::PickOption, &key_event) {
+                Some(index) => app.practice_choose_answer_option(index),
+                None => Ok(()),
+            }
+        },
+        Some(Action::ShowHelp) => app.open_help_popup(),
+        Some(Action::Back) => app.open_exit_popup(),
+        // The practice main area is the right pane.
+        Some(Action::GrowPane) => app.practice_decrease_body_left_size(),
+        Some(Action::ShrinkPane) => app.practice_increase_body_left_size(),
+        Some(Action::ResetPaneSize) => app.practice_reset_body_left_size(),
+        _ if app.answer_mode == AnswerMode::MultipleChoice => Ok(()),
         _ => {
             app.answer_input.handle_event(&event);
             Ok(())
-        }
+        },
+    }
+}
+
+fn handle_session_summary_input(app: &mut AppState, key: KeyEvent) -> Result<(), Box<dyn Error>> {
+    match app.keymap.get_action(&key, get_component_actions(&UiComponent::SessionSummary)) {
+        Some(Action::MoveUp) => app.summary_scroll_up(),
+        Some(Action::MoveDown) => app.summary_scroll_down(),
+        Some(Action::RetryMissed) => app.summary_practice_missed_questions(),
+        Some(Action::ExportSummary) => app.summary_export(),
+        Some(Action::Confirm | Action::Back) => app.summary_navigate_to_setup(),
+        _ => Ok(()),
     }
 }
 
 fn handle_exit_popup_input(app: &mut AppState, key: KeyEvent) -> Result<(), Box<dyn Error>> {
-    match key.code {
-        KeyCode::Enter => app.exit_app(),
-        KeyCode::Esc => app.close_popup(),
+    match app.keymap.get_action(&key, get_component_actions(&UiComponent::ExitPopup)) {
+        Some(Action::Confirm) => app.exit_app(),
+        Some(Action::Back) => app.close_popup(),
         _ => Ok(()),
     }
 }
 fn handle_notification_popup_input(app: &mut AppState, key: KeyEvent) -> Result<(), Box<dyn Error>> {
-    match key.code {
-        KeyCode::Enter => app.close_popup(),
-        KeyCode::Esc => app.close_popup(),
+    match app.keymap.get_action(&key, get_component_actions(&UiComponent::NotificationPopup)) {
+        Some(Action::Confirm | Action::Back) => app.close_popup(),
         _ => Ok(()),
     }
 }
 
+fn handle_problems_popup_input(app: &mut AppState, key: KeyEvent) -> Result<(), Box<dyn Error>> {
+    match app.keymap.get_action(&key, get_component_actions(&UiComponent::ProblemsPopup)) {
+        Some(Action::MoveUp) => app.problems_select_previous(),
+        Some(Action::MoveDown) => app.problems_select_next(),
+        Some(Action::Confirm | Action::Back) => app.close_popup(),
+        _ => Ok(()),
+    }
+}
+
+fn handle_help_popup_input(app: &mut AppState, key: KeyEvent) -> Result<(), Box<dyn Error>> {
+    match app.keymap.get_action(&key, get_component_actions(&UiComponent::HelpPopup)) {
+        Some(Action::MoveUp) => app.help_scroll_up(),
+        Some(Action::MoveDown) => app.help_scroll_down(),
+        Some(Action::Confirm | Action::ShowHelp | Action::Back) => app.close_popup(),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,144 @@
+use std::collections::HashSet;
+use crate::question_parser::GROUP_PATH_SEPARATOR;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GroupTreeRowKind {
+    Folder { is_expanded: bool },
+    Group,
+}
+
+/// A row of the group tree, either a folder or a group in it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupTreeRow {
+    /// The full path of the folder or the group name, e.g. `polish/lesson1/numbers`
+    pub path: String,
+    /// The number of folders above the row.
+    pub depth: usize,
+    pub kind: GroupTreeRowKind,
+}
+
+impl GroupTreeRow {
+    /// Returns the last part of the path, shown in the tree.
+    pub fn get_label(&self) -> &str {
+        self.path.rsplit(GROUP_PATH_SEPARATOR).next().unwrap_or(&self.path)
+    }
+
+    pub fn is_folder(&self) -> bool {
+        matches!(self.kind, GroupTreeRowKind::Folder { .. })
+    }
+}
+
+/// Returns the visible rows of the tree built from the sorted group names.
+/// The contents of the collapsed folders are hidden, unless every folder is expanded, e.g. to show the search hits.
+pub fn get_tree_rows(sorted_group_names: &[String], collapsed_folders: &HashSet<String>, is_fully_expanded: bool) -> Vec<GroupTreeRow> {
+    let mut rows = vec![];
+    let mut open_folders: Vec<&str> = vec![];
+    for group_name in sorted_group_names {
+        let mut parts: Vec<&str> = group_name.split(GROUP_PATH_SEPARATOR).collect();
+        parts.pop();
+        let common_depth = open_folders.iter()
+            .zip(&parts)
+            .take_while(|(open_folder, part)| open_folder == part)
+            .count();
+        open_folders.truncate(common_depth);
+        for depth in common_depth..parts.len() {
+            open_folders.push(parts[depth]);
+            let folder_path = parts[..=depth].join(&GROUP_PATH_SEPARATOR.to_string());
+            if is_hidden(&folder_path, collapsed_folders, is_fully_expanded) {
+                continue;
+            }
+            let is_expanded = is_fully_expanded || !collapsed_folders.contains(&folder_path);
+            rows.push(GroupTreeRow { path: folder_path, depth, kind: GroupTreeRowKind::Folder { is_expanded } });
+        }
+        if !is_hidden(group_name, collapsed_folders, is_fully_expanded) {
+            rows.push(GroupTreeRow { path: group_name.clone(), depth: parts.len(), kind: GroupTreeRowKind::Group });
+        }
+    }
+    rows
+}
+
+fn is_hidden(path: &str, collapsed_folders: &HashSet<String>, is_fully_expanded: bool) -> bool {
+    !is_fully_expanded && get_ancestor_folders(path).any(|folder| collapsed_folders.contains(folder))
+}
+
+/// Returns the folders containing the path, from the closest one.
+fn get_ancestor_folders(path: &str) -> impl Iterator<Item = &str> {
+    path.match_indices(GROUP_PATH_SEPARATOR)
+        .map(|(index, _)| &path[..index])
+        .collect::<Vec<&str>>()
+        .into_iter()
+        .rev()
+}
+
+pub fn get_parent_folder(path: &str) -> Option<&str> {
+    get_ancestor_folders(path).next()
+}
+
+/// Checks if the group is in the folder or in any of its subfolders.
+pub fn is_in_folder(group_name: &str, folder: &str) -> bool {
+    group_name.strip_prefix(folder)
+        .is_some_and(|rest| rest.starts_with(GROUP_PATH_SEPARATOR))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_group_names() -> Vec<String> {
+        ["german/numbers", "polish/lesson1/colors", "polish/lesson1/numbers", "polish/lesson2/numbers", "verbs"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    fn get_paths_with_depths(rows: &[GroupTreeRow]) -> Vec<(&str, usize)> {
+        rows.iter().map(|row| (row.path.as_str(), row.depth)).collect()
+    }
+
+    #[test]
+    fn tree_mirrors_the_folders_of_the_groups() {
+        // Given
+        let group_names = get_group_names();
+
+        // When
+        let rows = get_tree_rows(&group_names, &HashSet::new(), false);
+
+        // Then
+        assert_eq!(get_paths_with_depths(&rows), vec![
+            ("german", 0),
+            ("german/numbers", 1),
+            ("polish", 0),
+            ("polish/lesson1", 1),
+            ("polish/lesson1/colors", 2),
+            ("polish/lesson1/numbers", 2),
+            ("polish/lesson2", 1),
+            ("polish/lesson2/numbers", 2),
+            ("verbs", 0),
+        ]);
+        assert_eq!(rows[3].get_label(), "lesson1");
+        assert_eq!(rows[3].kind, GroupTreeRowKind::Folder { is_expanded: true });
+    }
+
+    #[test]
+    fn contents_of_collapsed_folders_are_hidden_unless_fully_expanded() {
+        // Given
+        let group_names = get_group_names();
+        let collapsed_folders = HashSet::from(["polish/lesson1".to_string()]);
+
+        // When
+        let rows = get_tree_rows(&group_names, &collapsed_folders, false);
+        let fully_expanded_rows = get_tree_rows(&group_names, &collapsed_folders, true);
+
+        // Then
+        assert_eq!(get_paths_with_depths(&rows)[2..5], [("polish", 0), ("polish/lesson1", 1), ("polish/lesson2", 1)]);
+        assert_eq!(rows[3].kind, GroupTreeRowKind::Folder { is_expanded: false });
+        assert_eq!(fully_expanded_rows.len(), 9);
+    }
+
+    #[test]
+    fn groups_are_only_in_their_own_folders() {
+        assert!(is_in_folder("polish/lesson1/numbers", "polish"));
+        assert!(!is_in_folder("polish2/numbers", "polish"));
+        assert_eq!(get_parent_folder("polish/lesson1/numbers"), Some("polish/lesson1"));
+        assert_eq!(get_parent_folder("verbs"), None);
+    }
+}
@@ -0,0 +1,105 @@
+use strum::EnumMessage;
+use crate::app::UiComponent;
+use crate::keymap::{get_component_actions, Keymap};
+use crate::practice::{RevealPenalties, ALMOST_CORRECT_PENALTY, DIACRITICS_MISMATCH_PENALTY, INCORRECT_ATTEMPT_PENALTY};
+
+/// The width of the key column in the list of actions.
+const KEYS_COLUMN_WIDTH: usize = 16;
+
+const QUESTION_FILE_LINES: &[&str] = &[
+    "Every line of a .sll file is a question and its answers, e.g. `dog | pies ; psa`",
+    "- The question and the answers are separated with a pipe | character.",
+    "- Multiple accepted answers are separated with a semicolon ; character, write \\; for a literal one.",
+    "- Lines starting with a hash # character are comments.",
+    "- Casing, extra spaces and punctuation do not matter when answering.",
+    "- Every file is a group, named after its path under the input path.",
+];
+
+pub fn get_component_title(component: &UiComponent) -> &'static str {
+    match component {
+        UiComponent::GroupSelector => "Group list",
+        UiComponent::QuestionSelector => "Question table",
+        UiComponent::SearchInput => "Search",
+        UiComponent::DeckNameInput => "Custom deck name",
+        UiComponent::PracticeControls => "Practice menu",
+        UiComponent::PracticeMain => "Practice",
+        UiComponent::SessionSummary => "Session summary",
+        UiComponent::ExitPopup => "Exit",
+        UiComponent::NotificationPopup => "Notification",
+        UiComponent::ProblemsPopup => "Problems",
+        UiComponent::HelpPopup => "Help",
+    }
+}
+
+/// Returns the lines of the help overlay of the component: every action with its keys,
+/// the format of the question files and how the penalties are scored. The lines ending with `:` are headers.
+pub fn get_help_lines(component: &UiComponent, keymap: &Keymap, reveal_penalties: &RevealPenalties) -> Vec<String> {
+    let mut lines = vec![format!("{} actions:", get_component_title(component))];
+    for action in get_component_actions(component) {
+        let keys = keymap.get_keys_text(&[*action]);
+        let keys = if keys.is_empty() { "(no keys)".to_string() } else { keys };
+        lines.push(format!("{keys:<KEYS_COLUMN_WIDTH$} {}", action.get_message().unwrap_or_default()));
+    }
+    if matches!(component, UiComponent::PracticeMain | UiComponent::SearchInput | UiComponent::DeckNameInput) {
+        lines.push("Keys that type characters are ignored while typing into a text input.".to_string());
+    }
+
+    lines.push(String::new());
+    lines.push("Question files:".to_string());
+    lines.extend(QUESTION_FILE_LINES.iter().map(|line| line.to_string()));
+
+    lines.push(String::new());
+    lines.push("Scoring:".to_string());
+    lines.push("Every question collects penalties until it is answered correctly:".to_string());
+    lines.extend([
+        ("incorrect attempt", INCORRECT_ATTEMPT_PENALTY),
+        ("almost correct attempt (a few typos)", ALMOST_CORRECT_PENALTY),
+        ("wrong accents in the Partial accents mode", DIACRITICS_MISMATCH_PENALTY),
+        ("revealing the clue", reveal_penalties.clue_reveal),
+        ("revealing the answer", reveal_penalties.answer_reveal),
+    ].map(|(reason, penalty)| format!("- {reason}: +{penalty}")));
+    lines.push(format!(
+        "Questions answered with fewer than {} penalties count as recalled for the spaced repetition, the others are asked again soon.",
+        reveal_penalties.clue_reveal,
+    ));
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use crate::keymap::{Action, KeysConfig};
+
+    #[test]
+    fn help_lists_the_actions_of_the_component_with_their_keys() {
+        // Given
+        let keymap = Keymap::new(&KeysConfig {
+            bindings: BTreeMap::from([(Action::StartPractice, vec!["F5".to_string()])]),
+            ..KeysConfig::default()
+        }).unwrap();
+
+        // When
+        let lines = get_help_lines(&UiComponent::GroupSelector, &keymap, &RevealPenalties::default());
+
+        // Then
+        assert_eq!(lines[0], "Group list actions:");
+        assert!(lines.contains(&format!("{:<KEYS_COLUMN_WIDTH$} Start practicing the selected groups", "F5")));
+        assert!(lines.contains(&format!("{:<KEYS_COLUMN_WIDTH$} Show this help", "?/F1")));
+        assert!(!lines.iter().any(|line| line.contains("Invert the marks")));
+    }
+
+    #[test]
+    fn help_shows_the_configured_penalties() {
+        // Given
+        let reveal_penalties = RevealPenalties { clue_reveal: 3, answer_reveal: 7 };
+
+        // When
+        let lines = get_help_lines(&UiComponent::PracticeMain, &Keymap::default(), &reveal_penalties);
+
+        // Then
+        assert!(lines.contains(&"- revealing the clue: +3".to_string()));
+        assert!(lines.contains(&"- revealing the answer: +7".to_string()));
+        assert!(lines.iter().any(|line| line.starts_with("1/2/3/4/5/6/7/8/9 ")));
+    }
+}
@@ -0,0 +1,113 @@
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// The name of the app's folder under the user's data and config directories.
+pub(crate) const APP_DIR_NAME: &str = "shellingo";
+
+/// Data saved as a JSON file, e.g. under the user's data directory, or kept in memory only.
+#[derive(Debug, Default)]
+pub struct JsonStore<T> {
+    path: Option<PathBuf>,
+    data: T,
+}
+
+impl<T: Serialize + DeserializeOwned + Default> JsonStore<T> {
+    /// Returns a store that is never written to disk.
+    pub fn in_memory() -> Self {
+        Self { path: None, data: T::default() }
+    }
+
+    /// Returns the location of the file under the user's data directory (e.g. `$XDG_DATA_HOME/shellingo`)
+    fn get_default_path(file_name: &str) -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join(APP_DIR_NAME).join(file_name))
+    }
+
+    /// Loads the data from the given file, or starts with empty data if the file does not exist yet.
+    pub fn load_from(path: PathBuf) -> Result<Self, Box<dyn Error>> {
+        let data = if path.exists() {
+            let contents = fs::read_to_string(&path)?;
+            serde_json::from_str(&contents)
+                .map_err(|e| format!("Cannot parse '{}': {}", path.display(), e))?
+        } else {
+            T::default()
+        };
+        Ok(Self { path: Some(path), data })
+    }
+
+    /// Loads the data from the file under the user's data directory. If it cannot be read, the data is kept
+    /// in memory only, so the unreadable file is not overwritten, and the reason is returned with the description
+    /// of the data, e.g. "Practice stats will not be saved. Data directory not found."
+    pub fn load_default(file_name: &str, description: &str) -> (Self, Option<String>) {
+        let Some(path) = Self::get_default_path(file_name) else {
+            return (Self::in_memory(), Some(format!("{description} will not be saved. Data directory not found.")));
+        };
+        match Self::load_from(path) {
+            Ok(store) => (store, None),
+            Err(e) => (Self::in_memory(), Some(format!("{description} will not be saved. {e}"))),
+        }
+    }
+
+    /// Writes the data to a temporary file next to the target first, so a crash while writing
+    /// never leaves a truncated file behind.
+    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+        let Some(path) = &self.path else { return Ok(()) };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut temp_file_name = path.file_name().unwrap_or_default().to_os_string();
+        temp_file_name.push(".tmp");
+        let temp_path = path.with_file_name(temp_file_name);
+        fs::write(&temp_path, serde_json::to_string_pretty(&self.data)?)?;
+        fs::rename(&temp_path, path)?;
+        Ok(())
+    }
+
+    pub fn get_path(&self) -> Option<&PathBuf> {
+        self.path.as_ref()
+    }
+
+    pub fn get_data(&self) -> &T {
+        &self.data
+    }
+
+    pub fn get_data_mut(&mut self) -> &mut T {
+        &mut self.data
+    }
+}
+
+/// Returns an unused file path for the store of the test.
+#[cfg(test)]
+pub(crate) fn get_temp_store_path(test_name: &str, file_name: &str) -> PathBuf {
+    let path = std::env::temp_dir()
+        .join(format!("shellingo_{}_{}", test_name, std::process::id()))
+        .join(file_name);
+    let _ = fs::remove_file(&path);
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use super::*;
+
+    #[test]
+    fn saving_replaces_the_file_without_leaving_a_temporary_file() {
+        // Given
+        let path = get_temp_store_path("saving_replaces_the_file_without_leaving_a_temporary_file", "store.json");
+        let mut store: JsonStore<BTreeMap<String, u32>> = JsonStore::load_from(path.clone()).unwrap();
+        store.get_data_mut().insert("first".to_string(), 1);
+        store.save().unwrap();
+
+        // When
+        store.get_data_mut().insert("second".to_string(), 2);
+        store.save().unwrap();
+        let actual: JsonStore<BTreeMap<String, u32>> = JsonStore::load_from(path.clone()).unwrap();
+
+        // Then
+        assert_eq!(actual.get_data().len(), 2);
+        assert!(!path.with_file_name("store.json.tmp").exists());
+    }
+}
@@ -0,0 +1,467 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Serialize};
+use strum::{EnumIter, EnumMessage, IntoEnumIterator};
+use crate::app::UiComponent;
+
+/// Something the user can do with a key press, independent of the key it is bound to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, EnumIter, EnumMessage, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    #[strum(message="Select the previous item or scroll up")]
+    MoveUp,
+    #[strum(message="Select the next item or scroll down")]
+    MoveDown,
+    #[strum(message="Collapse the selected folder, or switch to the left pane")]
+    MoveLeft,
+    #[strum(message="Expand the selected folder, or switch to the right pane")]
+    MoveRight,
+    #[strum(message="Switch between the left and the right pane")]
+    SwitchPanes,
+    #[strum(message="Select the group, folder or menu item, or mark the question")]
+    Select,
+    #[strum(message="Confirm, check the answer or close the popup")]
+    Confirm,
+    #[strum(message="Pick an answer in multiple-choice mode, the n-th key picks the n-th option")]
+    PickOption,
+    #[strum(message="Close the popup, clear the input or quit")]
+    Back,
+    #[strum(message="Start practicing the selected groups")]
+    StartPractice,
+    #[strum(message="Change the practice direction")]
+    ToggleDirection,
+    #[strum(message="Switch between typing and multiple choice")]
+    ToggleAnswerMode,
+    #[strum(message="Change the accents mode of every group")]
+    ToggleAccents,
+    #[strum(message="Change the accents mode of the selected group")]
+    ToggleGroupAccents,
+    #[strum(message="Search the groups, questions and answers")]
+    Search,
+    #[strum(message="Jump to the next search hit")]
+    NextSearchHit,
+    #[strum(message="Jump to the previous search hit")]
+    PreviousSearchHit,
+    #[strum(message="Select every group matching the search")]
+    SelectSearchHits,
+    #[strum(message="Switch between text and regex search")]
+    ToggleSearchMode,
+    #[strum(message="Invert the marks of the shown questions")]
+    InvertMarks,
+    #[strum(message="Save the marked questions as a custom deck")]
+    SaveDeck,
+    #[strum(message="Show the problems found in the question files")]
+    ShowProblems,
+    #[strum(message="Make the focused pane wider")]
+    GrowPane,
+    #[strum(message="Make the focused pane narrower")]
+    ShrinkPane,
+    #[strum(message="Reset the pane widths")]
+    ResetPaneSize,
+    #[strum(message="Practice the missed questions again")]
+    RetryMissed,
+    #[strum(message="Export the summary to a file")]
+    ExportSummary,
+    #[strum(message="Show this help")]
+    ShowHelp,
+}
+
+const GROUP_SELECTOR_ACTIONS: &[Action] = &[
+    Action::MoveUp, Action::MoveDown, Action::MoveLeft, Action::MoveRight, Action::SwitchPanes, Action::Select,
+    Action::StartPractice, Action::ToggleDirection, Action::ToggleAnswerMode, Action::ToggleAccents, Action::ToggleGroupAccents,
+    Action::Search, Action::NextSearchHit, Action::PreviousSearchHit, Action::SelectSearchHits, Action::SaveDeck,
+    Action::ShowProblems, Action::GrowPane, Action::ShrinkPane, Action::ResetPaneSize, Action::ShowHelp, Action::Back,
+];
+const QUESTION_SELECTOR_ACTIONS: &[Action] = &[
+    Action::MoveUp, Action::MoveDown, Action::MoveLeft, Action::MoveRight, Action::SwitchPanes, Action::Select,
+    Action::InvertMarks, Action::SaveDeck, Action::StartPractice, Action::ToggleDirection, Action::ToggleAnswerMode,
+    Action::Search, Action::NextSearchHit, Action::PreviousSearchHit, Action::SelectSearchHits,
+    Action::ShowProblems, Action::GrowPane, Action::ShrinkPane, Action::ResetPaneSize, Action::ShowHelp, Action::Back,
+];
+const SEARCH_INPUT_ACTIONS: &[Action] = &[Action::Confirm, Action::ToggleSearchMode, Action::Back];
+const DECK_NAME_INPUT_ACTIONS: &[Action] = &[Action::Confirm, Action::Back];
+const PRACTICE_CONTROLS_ACTIONS: &[Action] = &[
+    Action::MoveUp, Action::MoveDown, Action::MoveLeft, Action::MoveRight, Action::SwitchPanes, Action::Select,
+    Action::GrowPane, Action::ShrinkPane, Action::ResetPaneSize, Action::ShowHelp, Action::Back,
+];
+const PRACTICE_MAIN_ACTIONS: &[Action] = &[
+    Action::SwitchPanes, Action::Confirm, Action::PickOption, Action::GrowPane, Action::ShrinkPane, Action::ResetPaneSize, Action::ShowHelp, Action::Back,
+];
+const SESSION_SUMMARY_ACTIONS: &[Action] = &[
+    Action::MoveUp, Action::MoveDown, Action::RetryMissed, Action::ExportSummary, Action::Confirm, Action::Back,
+];
+const EXIT_POPUP_ACTIONS: &[Action] = &[Action::Confirm, Action::Back];
+const NOTIFICATION_POPUP_ACTIONS: &[Action] = &[Action::Confirm, Action::Back];
+const PROBLEMS_POPUP_ACTIONS: &[Action] = &[Action::MoveUp, Action::MoveDown, Action::Confirm, Action::Back];
+const HELP_POPUP_ACTIONS: &[Action] = &[Action::MoveUp, Action::MoveDown, Action::Confirm, Action::ShowHelp, Action::Back];
+
+/// Returns the actions available in the component, in the order their bindings are looked up.
+pub fn get_component_actions(component: &UiComponent) -> &'static [Action] {
+    match component {
+        UiComponent::GroupSelector => GROUP_SELECTOR_ACTIONS,
+        UiComponent::QuestionSelector => QUESTION_SELECTOR_ACTIONS,
+        UiComponent::SearchInput => SEARCH_INPUT_ACTIONS,
+        UiComponent::DeckNameInput => DECK_NAME_INPUT_ACTIONS,
+        UiComponent::PracticeControls => PRACTICE_CONTROLS_ACTIONS,
+        UiComponent::PracticeMain => PRACTICE_MAIN_ACTIONS,
+        UiComponent::SessionSummary => SESSION_SUMMARY_ACTIONS,
+        UiComponent::ExitPopup => EXIT_POPUP_ACTIONS,
+        UiComponent::NotificationPopup => NOTIFICATION_POPUP_ACTIONS,
+        UiComponent::ProblemsPopup => PROBLEMS_POPUP_ACTIONS,
+        UiComponent::HelpPopup => HELP_POPUP_ACTIONS,
+    }
+}
+
+/// A key with its modifiers, written like `Enter`, `Ctrl+n` or `k` in the config.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut key = text;
+        // A single character is a key on its own, even if it is `+`.
+        while key.chars().count() > 1 {
+            let Some((modifier, rest)) = key.split_once('+') else { break };
+            modifiers |= match modifier.to_lowercase().as_str() {
+                "ctrl" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                _ => return Err(format!("unknown modifier '{modifier}' in key '{text}'")),
+            };
+            key = rest;
+        }
+        let code = match key.to_lowercase().as_str() {
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "enter" => KeyCode::Enter,
+            "esc" => KeyCode::Esc,
+            "tab" => KeyCode::Tab,
+            "space" => KeyCode::Char(' '),
+            "backspace" => KeyCode::Backspace,
+            "delete" => KeyCode::Delete,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            lowercase => match lowercase.strip_prefix('f').and_then(|number| number.parse::<u8>().ok()) {
+                Some(number @ 1..=12) => KeyCode::F(number),
+                _ => {
+                    let mut chars = key.chars();
+                    match (chars.next(), chars.next()) {
+                        (Some(c), None) => KeyCode::Char(c),
+                        _ => return Err(format!("unknown key '{text}'")),
+                    }
+                }
+            },
+        };
+        Ok(Self { code, modifiers })
+    }
+
+    /// Checks if the key press matches the binding. Shift is part of the typed character, e.g. `N`.
+    pub fn matches(&self, key: &KeyEvent) -> bool {
+        self.code == key.code && self.modifiers == key.modifiers.difference(KeyModifiers::SHIFT)
+    }
+
+    /// Checks if the key types a character when a text input is focused.
+    pub fn is_text(&self) -> bool {
+        matches!(self.code, KeyCode::Char(_)) && self.modifiers.is_empty()
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt+")?;
+        }
+        match self.code {
+            KeyCode::Up => write!(f, "↑"),
+            KeyCode::Down => write!(f, "↓"),
+            KeyCode::Left => write!(f, "←"),
+            KeyCode::Right => write!(f, "→"),
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) => write!(f, "{c}"),
+            KeyCode::F(number) => write!(f, "F{number}"),
+            code => write!(f, "{code:?}"),
+        }
+    }
+}
+
+/// The built-in sets of bindings the user bindings are applied on.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeymapPreset {
+    /// Arrow keys, `j`/`k` and single letters.
+    #[default]
+    Default,
+    /// The default bindings, with `h`/`l` for moving left and right too.
+    Vim,
+    /// The default bindings, with `Ctrl+p`/`Ctrl+n`/`Ctrl+b`/`Ctrl+f` for moving around and `Ctrl+g` to go back.
+    Emacs,
+}
+
+impl KeymapPreset {
+    fn get_bindings(&self, action: Action) -> Vec<&'static str> {
+        let mut bindings = match action {
+            Action::MoveUp => vec!["Up", "k"],
+            Action::MoveDown => vec!["Down", "j"],
+            Action::MoveLeft => vec!["Left"],
+            Action::MoveRight => vec!["Right"],
+            Action::SwitchPanes => vec!["Tab"],
+            Action::Select => vec!["Enter", "Space"],
+            Action::Confirm => vec!["Enter"],
+            Action::PickOption => vec!["1", "2", "3", "4", "5", "6", "7", "8", "9"],
+            Action::Back => vec!["Esc"],
+            Action::StartPractice => vec!["p"],
+            Action::ToggleDirection => vec!["d"],
+            Action::ToggleAnswerMode => vec!["m"],
+            Action::ToggleAccents => vec!["A"],
+            Action::ToggleGroupAccents => vec!["a"],
+            Action::Search => vec!["/"],
+            Action::NextSearchHit => vec!["n"],
+            Action::PreviousSearchHit => vec!["N"],
+            Action::SelectSearchHits => vec!["S"],
+            Action::ToggleSearchMode => vec!["Tab"],
+            Action::InvertMarks => vec!["*"],
+            Action::SaveDeck => vec!["c"],
+            Action::ShowProblems => vec!["!"],
+            Action::GrowPane => vec!["+"],
+            Action::ShrinkPane => vec!["-"],
+            Action::ResetPaneSize => vec!["#"],
+            Action::RetryMissed => vec!["r"],
+            Action::ExportSummary => vec!["e"],
+            Action::ShowHelp => vec!["?", "F1"],
+        };
+        let extra_binding = match (self, action) {
+            (KeymapPreset::Vim, Action::MoveLeft) => Some("h"),
+            (KeymapPreset::Vim, Action::MoveRight) => Some("l"),
+            (KeymapPreset::Emacs, Action::MoveUp) => Some("Ctrl+p"),
+            (KeymapPreset::Emacs, Action::MoveDown) => Some("Ctrl+n"),
+            (KeymapPreset::Emacs, Action::MoveLeft) => Some("Ctrl+b"),
+            (KeymapPreset::Emacs, Action::MoveRight) => Some("Ctrl+f"),
+            (KeymapPreset::Emacs, Action::Back) => Some("Ctrl+g"),
+            (KeymapPreset::Emacs, Action::Search) => Some("Ctrl+s"),
+            _ => None,
+        };
+        bindings.extend(extra_binding);
+        bindings
+    }
+}
+
+/// The `[keys]` section of the config.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeysConfig {
+    pub preset: KeymapPreset,
+    /// Replaces the bindings of the preset for the given actions, e.g. `start_practice = ["p", "F5"]`
+    pub bindings: BTreeMap<Action, Vec<String>>,
+}
+
+/// The keys bound to every action.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keymap {
+    bindings: BTreeMap<Action, Vec<KeyBinding>>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::new(&KeysConfig::default()).expect("Default key bindings are expected to be valid")
+    }
+}
+
+impl Keymap {
+    pub fn new(config: &KeysConfig) -> Result<Self, String> {
+        let mut bindings = BTreeMap::new();
+        for action in Action::iter() {
+            let texts: Vec<&str> = match config.bindings.get(&action) {
+                Some(texts) => texts.iter().map(String::as_str).collect(),
+                None => config.preset.get_bindings(action),
+            };
+            let action_bindings = texts.into_iter()
+                .map(KeyBinding::parse)
+                .collect::<Result<Vec<KeyBinding>, String>>()?;
+            bindings.insert(action, action_bindings);
+        }
+        Ok(Self { bindings })
+    }
+
+    pub fn get_bindings(&self, action: Action) -> &[KeyBinding] {
+        self.bindings.get(&action).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Returns the first of the available actions bound to the key.
+    pub fn get_action(&self, key: &KeyEvent, actions: &[Action]) -> Option<Action> {
+        actions.iter()
+            .find(|action| self.get_bindings(**action).iter().any(|binding| binding.matches(key)))
+            .copied()
+    }
+
+    /// Returns the position of the key among the keys of the action, e.g. the option picked by a `PickOption` key.
+    pub fn get_binding_index(&self, action: Action, key: &KeyEvent) -> Option<usize> {
+        self.get_bindings(action).iter().position(|binding| binding.matches(key))
+    }
+
+    /// Returns the first of the available actions bound to the key,
+    /// ignoring the bindings that type characters into the focused text input.
+    pub fn get_text_input_action(&self, key: &KeyEvent, actions: &[Action]) -> Option<Action> {
+        actions.iter()
+            .find(|action| self.get_bindings(**action).iter().any(|binding| !binding.is_text() && binding.matches(key)))
+            .copied()
+    }
+
+    /// Returns the keys of the actions as shown in the help, e.g. `Enter/Space` for a single action
+    /// or `↑/↓` for the first keys of multiple actions.
+    pub fn get_keys_text(&self, actions: &[Action]) -> String {
+        let bindings: Vec<String> = match actions {
+            [action] => self.get_bindings(*action).iter().map(KeyBinding::to_string).collect(),
+            _ => actions.iter()
+                .filter_map(|action| self.get_bindings(*action).first())
+                .map(KeyBinding::to_string)
+                .collect(),
+        };
+        bindings.join("/")
+    }
+
+    /// Returns a help line like `[Tab] switch panes, [↑/↓] navigate`, skipping the actions without keys.
+    pub fn get_help_text(&self, hints: &[(&[Action], String)]) -> String {
+        hints.iter()
+            .filter_map(|(actions, label)| {
+                let keys = self.get_keys_text(actions);
+                (!keys.is_empty()).then(|| format!("[{keys}] {label}"))
+            })
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn presets_add_their_bindings_to_the_defaults() {
+        // Given
+        let vim_config = KeysConfig { preset: KeymapPreset::Vim, ..KeysConfig::default() };
+        let emacs_config = KeysConfig { preset: KeymapPreset::Emacs, ..KeysConfig::default() };
+
+        // When
+        let default_keymap = Keymap::default();
+        let vim_keymap = Keymap::new(&vim_config).unwrap();
+        let emacs_keymap = Keymap::new(&emacs_config).unwrap();
+
+        // Then
+        let j = get_key(KeyCode::Char('j'), KeyModifiers::NONE);
+        let l = get_key(KeyCode::Char('l'), KeyModifiers::NONE);
+        let ctrl_n = get_key(KeyCode::Char('n'), KeyModifiers::CONTROL);
+        assert_eq!(default_keymap.get_action(&j, GROUP_SELECTOR_ACTIONS), Some(Action::MoveDown));
+        assert_eq!(default_keymap.get_action(&l, GROUP_SELECTOR_ACTIONS), None);
+        assert_eq!(vim_keymap.get_action(&l, GROUP_SELECTOR_ACTIONS), Some(Action::MoveRight));
+        assert_eq!(emacs_keymap.get_action(&ctrl_n, GROUP_SELECTOR_ACTIONS), Some(Action::MoveDown));
+        assert_eq!(emacs_keymap.get_action(&get_key(KeyCode::Down, KeyModifiers::NONE), GROUP_SELECTOR_ACTIONS), Some(Action::MoveDown));
+    }
+
+    #[test]
+    fn user_bindings_replace_the_preset_bindings_of_the_action() {
+        // Given
+        let config = KeysConfig {
+            preset: KeymapPreset::Default,
+            bindings: BTreeMap::from([(Action::StartPractice, vec!["F5".to_string(), "Ctrl+p".to_string()])]),
+        };
+
+        // When
+        let keymap = Keymap::new(&config).unwrap();
+
+        // Then
+        assert_eq!(keymap.get_action(&get_key(KeyCode::Char('p'), KeyModifiers::NONE), GROUP_SELECTOR_ACTIONS), None);
+        assert_eq!(keymap.get_action(&get_key(KeyCode::F(5), KeyModifiers::NONE), GROUP_SELECTOR_ACTIONS), Some(Action::StartPractice));
+        assert_eq!(keymap.get_keys_text(&[Action::StartPractice]), "F5/Ctrl+p");
+    }
+
+    #[test]
+    fn same_key_triggers_the_action_available_in_the_component() {
+        // Given
+        let keymap = Keymap::default();
+        let enter = get_key(KeyCode::Enter, KeyModifiers::NONE);
+        let shifted_n = get_key(KeyCode::Char('N'), KeyModifiers::SHIFT);
+
+        // When
+        let actions = (
+            keymap.get_action(&enter, GROUP_SELECTOR_ACTIONS),
+            keymap.get_action(&enter, EXIT_POPUP_ACTIONS),
+            keymap.get_action(&shifted_n, GROUP_SELECTOR_ACTIONS),
+        );
+
+        // Then
+        assert_eq!(actions, (Some(Action::Select), Some(Action::Confirm), Some(Action::PreviousSearchHit)));
+    }
+
+    #[test]
+    fn character_bindings_are_ignored_in_text_inputs() {
+        // Given
+        let keymap = Keymap::default();
+
+        // When
+        let plus = keymap.get_text_input_action(&get_key(KeyCode::Char('+'), KeyModifiers::NONE), PRACTICE_MAIN_ACTIONS);
+        let enter = keymap.get_text_input_action(&get_key(KeyCode::Enter, KeyModifiers::NONE), PRACTICE_MAIN_ACTIONS);
+
+        // Then
+        assert_eq!((plus, enter), (None, Some(Action::Confirm)));
+    }
+
+    #[test]
+    fn answer_options_are_picked_by_the_position_of_the_key() {
+        // Given
+        let config = KeysConfig {
+            preset: KeymapPreset::Default,
+            bindings: BTreeMap::from([(Action::PickOption, vec!["F1".to_string(), "F2".to_string()])]),
+        };
+        let keymap = Keymap::new(&config).unwrap();
+        let f2 = get_key(KeyCode::F(2), KeyModifiers::NONE);
+
+        // When
+        let action = keymap.get_action(&f2, PRACTICE_MAIN_ACTIONS);
+        let index = keymap.get_binding_index(Action::PickOption, &f2);
+
+        // Then
+        assert_eq!((action, index), (Some(Action::PickOption), Some(1)));
+        assert_eq!(keymap.get_binding_index(Action::PickOption, &get_key(KeyCode::Char('2'), KeyModifiers::NONE)), None);
+    }
+
+    #[test]
+    fn keys_are_parsed_and_shown_like_in_the_config() {
+        assert_eq!(KeyBinding::parse("+"), Ok(KeyBinding { code: KeyCode::Char('+'), modifiers: KeyModifiers::NONE }));
+        assert_eq!(KeyBinding::parse("Ctrl++").unwrap().to_string(), "Ctrl++");
+        assert_eq!(KeyBinding::parse("alt+Enter").unwrap().to_string(), "Alt+Enter");
+        assert!(KeyBinding::parse("Hyper+x").is_err());
+        assert!(KeyBinding::parse("jk").is_err());
+    }
+
+    #[test]
+    fn help_text_is_generated_from_the_bindings() {
+        // Given
+        let keymap = Keymap::default();
+        let hints: [(&[Action], String); 3] = [
+            (&[Action::MoveUp, Action::MoveDown], "navigate".to_string()),
+            (&[Action::Select], "select".to_string()),
+            (&[], "nothing".to_string()),
+        ];
+
+        // When
+        let help_text = keymap.get_help_text(&hints);
+
+        // Then
+        assert_eq!(help_text, "[↑/↓] navigate, [Enter/Space] select");
+    }
+}
@@ -0,0 +1,190 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+use crate::config::{get_config_overrides_from, load_config};
+use crate::practice;
+use crate::practice::IgnoredSymbols;
+use crate::question_parser::{collect_groups_from_multiple_paths, get_group_naming_from, get_paths_from, read_all_questions_from_all_paths, GroupNaming, ParseDiagnostic, Severity};
+use crate::stats_store::StatsStore;
+
+const USAGE: &str = "Usage: shellingo lint [--format human|json] [--merge-same-names] [--set key=value] [paths...]";
+
+#[derive(Debug, PartialEq)]
+enum OutputFormat {
+    Human,
+    Json,
+}
+
+/// Runs the `lint` subcommand and returns the exit code:
+/// 0 if no problems were found, 1 if there were problems and 2 for invalid arguments.
+pub fn run(args: Vec<String>) -> i32 {
+    let (config_overrides, args) = get_config_overrides_from(args);
+    let (group_naming, args) = get_group_naming_from(args);
+    let (format, path_args) = match parse_args(args) {
+        Ok(parsed) => parsed,
+        Err(message) => {
+            eprintln!("{message}\n{USAGE}");
+            return 2;
+        }
+    };
+    let paths = get_paths_from(path_args);
+    // The questions are compared the same way as the answers in practice.
+    let (config, config_errors) = load_config(&paths, &config_overrides);
+    for error in config_errors {
+        eprintln!("Ignored invalid setting: {error}");
+    }
+    let diagnostics = lint_paths(paths, group_naming, &IgnoredSymbols::new(&config.matching.ignored_symbols).unwrap_or_default());
+    match format {
+        OutputFormat::Human => print_human_readable(&diagnostics),
+        OutputFormat::Json => match serde_json::to_string_pretty(&diagnostics) {
+            Ok(json) => println!("{json}"),
+            Err(e) => {
+                eprintln!("Cannot serialize the problems: {e}");
+                return 2;
+            }
+        },
+    }
+    if diagnostics.is_empty() { 0 } else { 1 }
+}
+
+fn parse_args(args: Vec<String>) -> Result<(OutputFormat, Vec<String>), String> {
+    let mut format = OutputFormat::Human;
+    let mut paths = vec![];
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => {
+                format = match args.next().as_deref() {
+                    Some("human") => OutputFormat::Human,
+                    Some("json") => OutputFormat::Json,
+                    other => return Err(format!("Invalid format: '{}'", other.unwrap_or_default())),
+                }
+            },
+            "--json" => format = OutputFormat::Json,
+            flag if flag.starts_with("--") => return Err(format!("Unknown option: '{flag}'")),
+            _ => paths.push(arg),
+        }
+    }
+    Ok((format, paths))
+}
+
+fn print_human_readable(diagnostics: &[ParseDiagnostic]) {
+    diagnostics.iter().for_each(|diagnostic| println!("{diagnostic}"));
+    if diagnostics.is_empty() {
+        println!("No problems found.");
+    } else {
+        let error_count = diagnostics.iter()
+            .filter(|diagnostic| diagnostic.severity == Severity::Error)
+            .count();
+        println!("{} error(s), {} warning(s)", error_count, diagnostics.len() - error_count);
+    }
+}
+
+/// Returns all problems found in the question files under the given paths, ordered by location.
+pub fn lint_paths(paths: Vec<PathBuf>, group_naming: GroupNaming, ignored_symbols: &IgnoredSymbols) -> Vec<ParseDiagnostic> {
+    let mut diagnostics = vec![];
+    let questions_by_groups = collect_groups_from_multiple_paths(paths, group_naming, &mut diagnostics);
+    let stats_store = StatsStore::in_memory();
+
+    // Normalized question -> group -> (file, answers)
+    let mut answers_by_questions: BTreeMap<String, BTreeMap<String, (String, BTreeSet<String>)>> = BTreeMap::new();
+    for (group_name, group) in &questions_by_groups {
+        for path in &group.paths {
+            let diagnostic_count = diagnostics.len();
+            let questions = read_all_questions_from_all_paths(group_name, std::slice::from_ref(path), &stats_store, &mut diagnostics);
+            if questions.is_empty() && diagnostics.len() == diagnostic_count {
+                diagnostics.push(ParseDiagnostic {
+                    file: path.display().to_string(),
+                    line: None,
+                    column: None,
+                    severity: Severity::Warning,
+                    message: "File contains no questions".to_string(),
+                });
+            }
+            for question in questions {
+                let question = question.borrow();
+                let (_, answers) = answers_by_questions.entry(practice::clean_string(&question.question, ignored_symbols))
+                    .or_default()
+                    .entry(group_name.clone())
+                    .or_insert_with(|| (path.display().to_string(), BTreeSet::new()));
+                answers.extend(question.answers.iter().cloned());
+            }
+        }
+    }
+
+    for (question, answers_by_groups) in answers_by_questions {
+        let distinct_answers: BTreeSet<&BTreeSet<String>> = answers_by_groups.values()
+            .map(|(_, answers)| answers)
+            .collect();
+        if distinct_answers.len() < 2 {
+            continue;
+        }
+        let (file, _) = answers_by_groups.values().next().unwrap(); // At least two groups are present
+        let conflicts = answers_by_groups.iter()
+            .map(|(group_name, (_, answers))| format!("'{}' in {}", answers.iter().cloned().collect::<Vec<_>>().join("', '"), group_name))
+            .collect::<Vec<_>>()
+            .join(", ");
+        diagnostics.push(ParseDiagnostic {
+            file: file.clone(),
+            line: None,
+            column: None,
+            severity: Severity::Warning,
+            message: format!("Question '{}' has conflicting answers across groups: {}", question, conflicts),
+        });
+    }
+
+    diagnostics.sort_by(|a, b| (&a.file, a.line, a.column).cmp(&(&b.file, b.line, b.column)));
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn all_problems_are_reported() {
+        // Given
+        let paths = vec![PathBuf::from("tests/fixtures/lint")];
+
+        // When
+        let actual = lint_paths(paths, GroupNaming::ByPath, &IgnoredSymbols::default());
+
+        // Then
+        let actual_summary: Vec<(&str, Option<usize>, Severity)> = actual.iter()
+            .map(|diagnostic| (diagnostic.file.as_str(), diagnostic.line, diagnostic.severity))
+            .collect();
+        assert_eq!(actual_summary, vec![
+            ("tests/fixtures/lint/empty.sll", None, Severity::Warning),
+            ("tests/fixtures/lint/malformed.sll", Some(1), Severity::Error),
+            ("tests/fixtures/lint/non_utf8.sll", None, Severity::Error),
+            ("tests/fixtures/lint/polish/numbers_pl.sll", None, Severity::Warning),
+        ]);
+        assert_eq!(
+            actual[3].message,
+            "Question '1' has conflicting answers across groups: 'jeden' in polish/numbers_pl, 'uno' in spanish/numbers_es"
+        );
+    }
+
+    #[test]
+    fn valid_files_have_no_problems() {
+        // Given
+        let paths = vec![PathBuf::from("tests/fixtures/nested_with_mixed_files")];
+
+        // When
+        let actual = lint_paths(paths, GroupNaming::ByPath, &IgnoredSymbols::default());
+
+        // Then
+        assert_eq!(actual, vec![]);
+    }
+
+    #[test]
+    fn format_option_is_parsed() {
+        // Given
+        let args = vec!["--format".to_string(), "json".to_string(), "path".to_string()];
+
+        // When
+        let actual = parse_args(args);
+
+        // Then
+        assert_eq!(actual, Ok((OutputFormat::Json, vec!["path".to_string()])));
+    }
+}
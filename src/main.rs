@@ -1,4 +1,8 @@
 use std::env;
+use std::io::stdout;
+use std::process;
+use ratatui::crossterm::event::{DisableMouseCapture, EnableMouseCapture};
+use ratatui::crossterm::execute;
 use crate::app::AppState;
 
 mod app;
@@ -10,13 +14,32 @@ mod ui_practice_phase;
 mod ui_shared;
 mod question;
 mod practice;
+mod lint;
+mod json_store;
+mod stats_store;
+mod session_summary;
+mod blitz;
+mod search;
+mod custom_deck;
+mod group_tree;
+mod session_store;
+mod config;
+mod keymap;
+mod help;
+mod mouse;
+mod theme;
 
 fn main() -> std::io::Result<()> {
-    ratatui::run(|terminal| {
-        let mut args: Vec<String> = env::args().collect();
-        // Clear the default argument containing the full executable path
+    let mut args: Vec<String> = env::args().collect();
+    // Clear the default argument containing the full executable path
+    args.remove(0);
+    if args.first().is_some_and(|arg| arg == "lint") {
         args.remove(0);
+        process::exit(lint::run(args));
+    }
+    ratatui::run(|terminal| {
         let mut app = AppState::new(args);
+        let _mouse_capture = app.config.input.mouse_capture.then(MouseCaptureGuard::new).transpose()?;
         loop {
             terminal.draw(|frame| ui::draw_ui(frame, &mut app))?;
             if let Err(e) = events::handle_input(&mut app) {
@@ -27,3 +50,19 @@ fn main() -> std::io::Result<()> {
         Ok(())
     })
 }
+
+/// Captures the mouse until dropped, so the capture is released also when drawing fails or the app panics.
+struct MouseCaptureGuard;
+
+impl MouseCaptureGuard {
+    fn new() -> std::io::Result<Self> {
+        execute!(stdout(), EnableMouseCapture)?;
+        Ok(Self)
+    }
+}
+
+impl Drop for MouseCaptureGuard {
+    fn drop(&mut self) {
+        let _ = execute!(stdout(), DisableMouseCapture);
+    }
+}
@@ -0,0 +1,112 @@
+use std::time::{Duration, Instant};
+use ratatui::layout::{Position, Rect};
+
+/// Two clicks on the same cell within this time are a double click.
+const DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(400);
+
+/// The screen areas of the panes, updated on every draw to find what was clicked.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MouseAreas {
+    /// The group list or the practice menu.
+    pub body_left: Rect,
+    /// The question table and the box chart, or the practice area.
+    pub body_right: Rect,
+    pub question_table: Rect,
+}
+
+/// Remembers the last click, as the terminal only reports single clicks.
+#[derive(Debug, Default)]
+pub struct ClickTracker {
+    last_click: Option<(Instant, Position)>,
+}
+
+impl ClickTracker {
+    /// Registers a click and returns whether it completes a double click.
+    pub fn register_click(&mut self, now: Instant, position: Position) -> bool {
+        let is_double_click = self.last_click
+            .is_some_and(|(time, last_position)| last_position == position && now.duration_since(time) <= DOUBLE_CLICK_INTERVAL);
+        // A third click starts a new double click.
+        self.last_click = if is_double_click { None } else { Some((now, position)) };
+        is_double_click
+    }
+}
+
+/// Returns the index of the clicked row of a bordered list or table, scrolled by the offset.
+pub fn get_row_index(area: Rect, header_height: u16, offset: usize, position: Position) -> Option<usize> {
+    let first_row = area.y + 1 + header_height;
+    let last_row = area.bottom().saturating_sub(1);
+    let is_inside = position.x > area.x && position.x < area.right().saturating_sub(1)
+        && (first_row..last_row).contains(&position.y);
+    is_inside.then(|| offset + (position.y - first_row) as usize)
+}
+
+/// Checks if the position is on the borders between the left and the right pane.
+pub fn is_on_divider(body_left: Rect, position: Position) -> bool {
+    let divider_columns = body_left.right().saturating_sub(1)..=body_left.right();
+    divider_columns.contains(&position.x) && (body_left.y..body_left.bottom()).contains(&position.y)
+}
+
+/// Returns the width of the left pane when its right border is dragged to the column.
+pub fn get_dragged_left_pane_width(body_left: Rect, body_right: Rect, column: u16) -> u16 {
+    let max_width = (body_left.width + body_right.width).saturating_sub(2);
+    (column.saturating_sub(body_left.x) + 1).clamp(2, max_width.max(2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn second_click_on_the_same_cell_in_time_is_a_double_click() {
+        // Given
+        let mut tracker = ClickTracker::default();
+        let start = Instant::now();
+        let position = Position::new(3, 4);
+
+        // When
+        let clicks = [
+            tracker.register_click(start, position),
+            tracker.register_click(start + Duration::from_millis(200), position),
+            tracker.register_click(start + Duration::from_millis(300), position),
+            tracker.register_click(start + Duration::from_millis(400), Position::new(3, 5)),
+            tracker.register_click(start + Duration::from_millis(1000), Position::new(3, 5)),
+        ];
+
+        // Then
+        assert_eq!(clicks, [false, true, false, false, false]);
+    }
+
+    #[test]
+    fn clicked_row_is_counted_from_the_first_visible_row() {
+        // Given
+        let area = Rect::new(10, 5, 20, 10);
+
+        // When
+        let rows = [
+            get_row_index(area, 0, 0, Position::new(12, 6)),
+            get_row_index(area, 0, 7, Position::new(12, 8)),
+            get_row_index(area, 1, 0, Position::new(12, 6)),
+            get_row_index(area, 1, 0, Position::new(12, 7)),
+            get_row_index(area, 0, 0, Position::new(12, 14)),
+            get_row_index(area, 0, 0, Position::new(10, 6)),
+        ];
+
+        // Then
+        assert_eq!(rows, [Some(0), Some(9), None, Some(0), None, None]);
+    }
+
+    #[test]
+    fn divider_is_dragged_within_the_body() {
+        // Given
+        let body_left = Rect::new(0, 3, 45, 20);
+        let body_right = Rect::new(45, 3, 75, 20);
+
+        // When
+        let is_on_divider = (is_on_divider(body_left, Position::new(44, 10)), is_on_divider(body_left, Position::new(43, 10)));
+        let widths = [0, 29, 200].map(|column| get_dragged_left_pane_width(body_left, body_right, column));
+
+        // Then
+        assert_eq!(is_on_divider, (true, false));
+        assert_eq!(widths, [2, 30, 118]);
+    }
+}
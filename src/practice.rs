@@ -1,17 +1,119 @@
 use std::cell::RefCell;
+use std::collections::{HashSet, VecDeque};
 use std::rc::Rc;
 use std::sync::LazyLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use rand::seq::{IndexedRandom, SliceRandom};
 use regex::Regex;
+use serde::{Deserialize, Serialize};
+use strum::{EnumIter, EnumMessage, IntoEnumIterator};
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
 use crate::question::Question;
 
-pub const CLUE_REVEAL_PENALTY: u16 = 5;
-pub const ANSWER_REVEAL_PENALTY: u16 = 10;
+const CLUE_REVEAL_PENALTY: u16 = 5;
+const ANSWER_REVEAL_PENALTY: u16 = 10;
+pub const INCORRECT_ATTEMPT_PENALTY: u16 = 1;
+pub const ALMOST_CORRECT_PENALTY: u16 = 1;
+pub const DIACRITICS_MISMATCH_PENALTY: u16 = 1;
+pub const ANSWER_OPTION_COUNT: usize = 4;
+pub const DEFAULT_MASTERY_STREAK: u16 = 3;
+pub const LEITNER_BOX_COUNT: u8 = 5;
+pub const MAX_MASTERY_STREAK: u16 = 5;
+/// The number of questions asked before a wrongly answered question is asked again in a mastery round.
+const MASTERY_REQUEUE_DISTANCE: usize = 3;
+pub const DEFAULT_TYPO_TOLERANCE: f32 = 0.2;
+/// The symbols removed before comparing the attempts and answers, unless configured otherwise.
+pub const DEFAULT_IGNORED_SYMBOLS: &str = "?,!.:;";
+/// Every this much of the median response time counts as one error in the difficulty score.
+const SLOW_RESPONSE_TIME_PER_ERROR: Duration = Duration::from_secs(5);
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+const MIN_EASE_FACTOR: f32 = 1.3;
+const DEFAULT_EASE_FACTOR: f32 = 2.5;
 static REGEX_MULTIPLE_SPACES: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\s+").unwrap());
-static REGEX_SYMBOLS_TO_REMOVE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"[?,!.:;]+").unwrap());
+/// The stats keys are normalized the same way whatever symbols are ignored by the config, so they are kept when it changes.
+static DEFAULT_SYMBOLS_TO_REMOVE: LazyLock<IgnoredSymbols> = LazyLock::new(IgnoredSymbols::default);
 
-pub fn reveal_clue(question: Rc<RefCell<Question>>) -> String {
-    question.borrow().answers.iter()
-        .next()
+/// The penalties added to the error count of a question when its clue or answer is revealed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RevealPenalties {
+    pub clue_reveal: u16,
+    pub answer_reveal: u16,
+}
+
+impl Default for RevealPenalties {
+    fn default() -> Self {
+        RevealPenalties {
+            clue_reveal: CLUE_REVEAL_PENALTY,
+            answer_reveal: ANSWER_REVEAL_PENALTY,
+        }
+    }
+}
+
+/// The side of a question that has to be answered.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// The question is asked and one of its answers is expected.
+    #[default]
+    Forward,
+    /// The answers are asked and the question is expected.
+    Reverse,
+}
+
+/// The practice direction chosen by the user.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, EnumMessage, Serialize, Deserialize)]
+pub enum DirectionSetting {
+    #[default]
+    #[strum(message="Forward")]
+    Forward,
+    #[strum(message="Reverse")]
+    Reverse,
+    #[strum(message="Random")]
+    Random,
+}
+
+impl DirectionSetting {
+    pub fn next(&self) -> DirectionSetting {
+        match self {
+            DirectionSetting::Forward => DirectionSetting::Reverse,
+            DirectionSetting::Reverse => DirectionSetting::Random,
+            DirectionSetting::Random => DirectionSetting::Forward,
+        }
+    }
+
+    /// Returns the directions a question can be asked in with this setting.
+    pub fn get_directions(&self) -> &'static [Direction] {
+        match self {
+            DirectionSetting::Forward => &[Direction::Forward],
+            DirectionSetting::Reverse => &[Direction::Reverse],
+            DirectionSetting::Random => &[Direction::Forward, Direction::Reverse],
+        }
+    }
+}
+
+pub fn get_prompt(question: &Question, direction: Direction) -> String {
+    match direction {
+        Direction::Forward => question.question.clone(),
+        Direction::Reverse => get_expected_answers(question, Direction::Forward).join(" / "),
+    }
+}
+
+/// Returns the accepted answers in a fixed order.
+pub fn get_expected_answers(question: &Question, direction: Direction) -> Vec<String> {
+    match direction {
+        Direction::Forward => {
+            let mut answers: Vec<String> = question.answers.iter().cloned().collect();
+            answers.sort();
+            answers
+        },
+        Direction::Reverse => vec![question.question.clone()],
+    }
+}
+
+/// Returns a clue for every accepted answer variant.
+pub fn reveal_clues(question: Rc<RefCell<Question>>, direction: Direction) -> Vec<String> {
+    let clues: Vec<String> = get_expected_answers(&question.borrow(), direction).iter()
         .map(|answer|
             answer.chars()
                 .enumerate()
@@ -19,42 +121,684 @@ pub fn reveal_clue(question: Rc<RefCell<Question>>) -> String {
                 .map(|(index, c)|  if c == ' ' || index % 2 == 0 { c } else { '■' })
                 .collect::<String>()
         )
-        .unwrap_or(format!("Cannot generate clue for the answer(s): '{:?}'", question))
+        .collect();
+    if clues.is_empty() {
+        vec![format!("Cannot generate clue for the answer(s): '{:?}'", question)]
+    } else {
+        clues
+    }
 }
 
-pub fn reveal_answer(question: Rc<RefCell<Question>>) -> String {
-    question.borrow_mut().answers.iter()
-        .next()
-        .unwrap_or_else(|| panic!("Cannot reveal answer(s): '{:?}'", question))
-        .clone()
+/// Returns every accepted answer variant.
+pub fn reveal_answers(question: Rc<RefCell<Question>>, direction: Direction) -> Vec<String> {
+    let answers = get_expected_answers(&question.borrow(), direction);
+    if answers.is_empty() {
+        panic!("Cannot reveal answer(s): '{:?}'", question)
+    }
+    answers
 }
 
-pub fn get_hardest_questions_in_round(questions: &[Rc<RefCell<Question>>], limit: usize) -> Vec<Rc<RefCell<Question>>> {
-    // Reverse sort (hardest first)
+pub fn get_hardest_questions_in_round(questions: &[Rc<RefCell<Question>>], limit: usize, directions: &[Direction]) -> Vec<Rc<RefCell<Question>>> {
+    // Reverse sort (hardest first), the difficulty in previous sessions breaks the ties.
     let mut refs = questions.to_vec();
-    refs.sort_by_key(|question|
-        std::cmp::Reverse(question.borrow().get_error_count_for_round()));
+    refs.sort_by_key(|question| std::cmp::Reverse(get_difficulty_score(&question.borrow(), directions)));
     // Keep only the first X items
     refs.into_iter()
         .take(limit)
         .collect()
 }
 
-pub fn is_attempt_successful(attempt: &str, question: Rc<RefCell<Question>>) -> bool {
-    let cleaned_attempt = clean_string(attempt);
-    question.borrow_mut().answers.iter()
-        .map(|answer| clean_string(answer) == cleaned_attempt)
-        .reduce(|a, b| a || b)
-        .unwrap_or(false)
+/// Returns the difficulty of the question in the current round and in all sessions:
+/// the number of errors, with slow answers counted as extra errors.
+pub fn get_difficulty_score(question: &Question, directions: &[Direction]) -> (u32, u32) {
+    let round_response_times: Vec<Duration> = directions.iter()
+        .flat_map(|direction| question.get_response_times_for_round(*direction).to_vec())
+        .collect();
+    let all_response_times: Vec<Duration> = directions.iter()
+        .flat_map(|direction| question.get_response_times(*direction))
+        .collect();
+    let round_errors: u32 = directions.iter()
+        .map(|direction| question.get_error_count_for_round(*direction) as u32)
+        .sum();
+    let all_errors: u32 = directions.iter()
+        .map(|direction| question.get_error_count_sum(*direction) as u32)
+        .sum();
+    (
+        round_errors + get_slowness_penalty(&round_response_times),
+        all_errors + get_slowness_penalty(&all_response_times),
+    )
+}
+
+fn get_slowness_penalty(response_times: &[Duration]) -> u32 {
+    get_median_duration(response_times)
+        .map(|median| (median.as_millis() / SLOW_RESPONSE_TIME_PER_ERROR.as_millis()) as u32)
+        .unwrap_or(0)
+}
+
+/// Returns the mean response time of the question in the given directions, `None` if it was never answered.
+pub fn get_average_response_time(question: &Question, directions: &[Direction]) -> Option<Duration> {
+    let response_times: Vec<Duration> = directions.iter()
+        .flat_map(|direction| question.get_response_times(*direction))
+        .collect();
+    get_mean_duration(&response_times)
+}
+
+pub fn get_mean_duration(durations: &[Duration]) -> Option<Duration> {
+    if durations.is_empty() {
+        return None;
+    }
+    Some(durations.iter().sum::<Duration>() / durations.len() as u32)
+}
+
+pub fn get_median_duration(durations: &[Duration]) -> Option<Duration> {
+    if durations.is_empty() {
+        return None;
+    }
+    let mut sorted = durations.to_vec();
+    sorted.sort();
+    let middle = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        Some((sorted[middle - 1] + sorted[middle]) / 2)
+    } else {
+        Some(sorted[middle])
+    }
+}
+
+/// Spaced-repetition state of a question, updated with the SM-2 algorithm.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReviewSchedule {
+    pub ease_factor: f32,
+    pub interval_days: u32,
+    pub repetitions: u32,
+    /// Days since the Unix epoch, `None` if the question was never reviewed.
+    pub due_day: Option<u64>,
+    /// Seconds since the Unix epoch, `None` if the question was never reviewed.
+    pub last_review_timestamp: Option<u64>,
+}
+
+impl Default for ReviewSchedule {
+    fn default() -> Self {
+        ReviewSchedule {
+            ease_factor: DEFAULT_EASE_FACTOR,
+            interval_days: 0,
+            repetitions: 0,
+            due_day: None,
+            last_review_timestamp: None,
+        }
+    }
+}
+
+impl ReviewSchedule {
+    pub fn is_due(&self, today: u64) -> bool {
+        self.due_day.is_none_or(|due_day| due_day <= today)
+    }
+
+    /// Schedules the next review based on the quality of the recall, from 0 (blackout) to 5 (perfect).
+    pub fn review(&mut self, quality: u8, timestamp: u64) {
+        let quality = quality.min(5);
+        if quality >= 3 {
+            self.interval_days = match self.repetitions {
+                0 => 1,
+                1 => 6,
+                _ => (self.interval_days as f32 * self.ease_factor).round() as u32,
+            };
+            self.repetitions += 1;
+        } else {
+            // Start over with short intervals, but keep the ease factor.
+            self.repetitions = 0;
+            self.interval_days = 1;
+        }
+        let quality_gap = (5 - quality) as f32;
+        self.ease_factor = (self.ease_factor + 0.1 - quality_gap * (0.08 + quality_gap * 0.02))
+            .max(MIN_EASE_FACTOR);
+        // Failed questions stay due, so they are repeated in the same session.
+        let days_until_due = if quality >= 3 { self.interval_days as u64 } else { 0 };
+        self.due_day = Some(timestamp / SECONDS_PER_DAY + days_until_due);
+        self.last_review_timestamp = Some(timestamp);
+    }
+}
+
+pub fn get_current_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+pub fn get_current_day() -> u64 {
+    get_current_timestamp() / SECONDS_PER_DAY
+}
+
+/// Maps the penalties collected before answering a question correctly to an SM-2 recall quality.
+pub fn get_review_quality(penalty: u16, reveal_penalties: &RevealPenalties) -> u8 {
+    match penalty {
+        0 => 5,
+        1 => 4,
+        p if p < reveal_penalties.clue_reveal => 3,
+        p if p < reveal_penalties.answer_reveal => 2,
+        _ => 0,
+    }
+}
+
+/// Returns the directions in which the question is due for review.
+pub fn get_due_directions(question: &Question, today: u64, directions: &[Direction]) -> Vec<Direction> {
+    directions.iter()
+        .filter(|direction| question.get_schedule(**direction).is_due(today))
+        .cloned()
+        .collect()
+}
+
+/// Returns the questions due for review in any of the directions,
+/// the most overdue first and never reviewed ones last.
+pub fn get_due_questions(questions: &[Rc<RefCell<Question>>], today: u64, directions: &[Direction]) -> Vec<Rc<RefCell<Question>>> {
+    let mut due_questions: Vec<Rc<RefCell<Question>>> = questions.iter()
+        .filter(|question| !get_due_directions(&question.borrow(), today, directions).is_empty())
+        .cloned()
+        .collect();
+    due_questions.sort_by_key(|question| {
+        let question = question.borrow();
+        let earliest_due_day = get_due_directions(&question, today, directions).iter()
+            .map(|direction| question.get_schedule(*direction).due_day)
+            .min()
+            .flatten();
+        (earliest_due_day.is_none(), earliest_due_day)
+    });
+    due_questions
 }
 
-fn clean_string(response: &str) -> String {
+/// Position of a question in the Leitner system. New and failed questions are in the first box,
+/// every correct answer moves them to the next, less frequently practiced box.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LeitnerState {
+    /// From 1 to `LEITNER_BOX_COUNT`.
+    pub box_number: u8,
+    /// The number of Leitner sessions started since the question was last practiced.
+    pub sessions_waited: u32,
+}
+
+impl Default for LeitnerState {
+    fn default() -> Self {
+        LeitnerState {
+            box_number: 1,
+            sessions_waited: 0,
+        }
+    }
+}
+
+impl LeitnerState {
+    /// Box 1 is practiced in every session, box 2 in every second, box 3 in every fourth, and so on.
+    pub fn get_session_interval(&self) -> u32 {
+        1 << (self.box_number.clamp(1, LEITNER_BOX_COUNT) - 1)
+    }
+
+    pub fn is_due(&self) -> bool {
+        self.sessions_waited >= self.get_session_interval()
+    }
+
+    /// Returns the number of sessions without the question before the one it is due in.
+    fn get_sessions_until_due(&self) -> u32 {
+        self.get_session_interval().saturating_sub(self.sessions_waited.saturating_add(1))
+    }
+
+    pub fn review(&mut self, is_correct: bool) {
+        self.box_number = if is_correct { (self.box_number + 1).min(LEITNER_BOX_COUNT) } else { 1 };
+        self.sessions_waited = 0;
+    }
+}
+
+/// Starts the next Leitner session in which any of the questions is due and returns the due ones.
+/// The sessions without due questions are skipped, as they would not be practiced,
+/// and nothing changes if there are no questions.
+pub fn start_leitner_session(questions: &[Rc<RefCell<Question>>], directions: &[Direction]) -> Vec<Rc<RefCell<Question>>> {
+    let Some(skipped_sessions) = questions.iter()
+        .flat_map(|question| directions.iter()
+            .map(|direction| question.borrow().get_leitner(*direction).get_sessions_until_due())
+            .collect::<Vec<u32>>())
+        .min() else { return vec![] };
+    for question in questions {
+        let mut question = question.borrow_mut();
+        for direction in directions {
+            let leitner = question.get_leitner_mut(*direction);
+            leitner.sessions_waited = leitner.sessions_waited.saturating_add(skipped_sessions + 1);
+        }
+    }
+    questions.iter()
+        .filter(|question| !get_leitner_due_directions(&question.borrow(), directions).is_empty())
+        .cloned()
+        .collect()
+}
+
+/// Returns the directions in which the question is due in the current Leitner session.
+pub fn get_leitner_due_directions(question: &Question, directions: &[Direction]) -> Vec<Direction> {
+    directions.iter()
+        .filter(|direction| question.get_leitner(**direction).is_due())
+        .cloned()
+        .collect()
+}
+
+/// Returns the number of questions in each Leitner box, counting every direction separately.
+pub fn get_leitner_box_counts(questions: &[Rc<RefCell<Question>>], directions: &[Direction]) -> [u64; LEITNER_BOX_COUNT as usize] {
+    let mut counts = [0; LEITNER_BOX_COUNT as usize];
+    for question in questions {
+        for direction in directions {
+            let box_number = question.borrow().get_leitner(*direction).box_number.clamp(1, LEITNER_BOX_COUNT);
+            counts[box_number as usize - 1] += 1;
+        }
+    }
+    counts
+}
+
+/// A round that asks the questions until each of them is answered correctly a few times in a row.
+#[derive(Debug, Clone)]
+pub struct MasteryRound {
+    /// The questions to ask with their current streaks of correct answers, the current question first.
+    queue: VecDeque<(Rc<RefCell<Question>>, u16)>,
+    target_streak: u16,
+    question_count: usize,
+}
+
+impl MasteryRound {
+    pub fn new(questions: Vec<Rc<RefCell<Question>>>, target_streak: u16) -> Self {
+        Self {
+            question_count: questions.len(),
+            queue: questions.into_iter().map(|question| (question, 0)).collect(),
+            target_streak: target_streak.max(1),
+        }
+    }
+
+    /// Retires the current question if it reached the target streak, otherwise puts it back to the queue:
+    /// to the end after a correct answer, or a few questions later after a wrong one.
+    pub fn answer_current_question(&mut self, is_correct: bool) {
+        let Some((question, streak)) = self.queue.pop_front() else { return };
+        if !is_correct {
+            self.queue.insert(MASTERY_REQUEUE_DISTANCE.min(self.queue.len()), (question, 0));
+        } else if streak + 1 < self.target_streak {
+            self.queue.push_back((question, streak + 1));
+        }
+    }
+
+    /// Returns the remaining questions in the order they are asked.
+    pub fn get_questions(&self) -> Vec<Rc<RefCell<Question>>> {
+        self.queue.iter().map(|(question, _)| question.clone()).collect()
+    }
+
+    pub fn get_mastered_count(&self) -> usize {
+        self.question_count - self.queue.len()
+    }
+
+    pub fn get_question_count(&self) -> usize {
+        self.question_count
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.queue.is_empty()
+    }
+}
+
+/// Settings that control how strictly the attempts are graded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GradingSettings {
+    /// The number of typos accepted as "almost correct", relative to the length of the answer.
+    /// E.g. 0.2 accepts one typo for every five characters, 0 disables typo tolerance.
+    pub typo_tolerance: f32,
+    pub diacritics_mode: DiacriticsMode,
+    /// The language of the special letters folded when the diacritics are ignored.
+    pub language: Language,
+    pub ignored_symbols: IgnoredSymbols,
+}
+
+impl Default for GradingSettings {
+    fn default() -> Self {
+        GradingSettings {
+            typo_tolerance: DEFAULT_TYPO_TOLERANCE,
+            diacritics_mode: DiacriticsMode::default(),
+            language: Language::default(),
+            ignored_symbols: IgnoredSymbols::default(),
+        }
+    }
+}
+
+/// The symbols removed before comparing the attempts and answers.
+#[derive(Debug, Clone)]
+pub struct IgnoredSymbols {
+    symbols: String,
+    /// `None` if no symbols are ignored.
+    regex: Option<Regex>,
+}
+
+impl IgnoredSymbols {
+    pub fn new(symbols: &str) -> Result<Self, String> {
+        let regex = if symbols.is_empty() {
+            None
+        } else {
+            Some(Regex::new(&format!("[{}]+", regex::escape(symbols))).map_err(|e| e.to_string())?)
+        };
+        Ok(IgnoredSymbols { symbols: symbols.to_string(), regex })
+    }
+}
+
+impl Default for IgnoredSymbols {
+    fn default() -> Self {
+        IgnoredSymbols::new(DEFAULT_IGNORED_SYMBOLS).expect("Default ignored symbols are expected to be valid")
+    }
+}
+
+impl PartialEq for IgnoredSymbols {
+    fn eq(&self, other: &Self) -> bool {
+        self.symbols == other.symbols
+    }
+}
+
+/// Defines how attempts that only differ from an answer in accents and special letters
+/// (e.g. "piec" for "pięć") are graded.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, EnumMessage, Serialize, Deserialize)]
+pub enum DiacriticsMode {
+    /// Graded like any other difference.
+    #[default]
+    #[strum(message="Strict")]
+    Strict,
+    /// Accepted with a warning.
+    #[strum(message="Warn")]
+    Warn,
+    /// Accepted, but counted as a partial error.
+    #[strum(message="Partial")]
+    PartialError,
+}
+
+impl DiacriticsMode {
+    pub fn next(&self) -> DiacriticsMode {
+        match self {
+            DiacriticsMode::Strict => DiacriticsMode::Warn,
+            DiacriticsMode::Warn => DiacriticsMode::PartialError,
+            DiacriticsMode::PartialError => DiacriticsMode::Strict,
+        }
+    }
+}
+
+/// The languages with special letters that have no decomposed form, e.g. Polish "ł".
+/// Their accents are removed regardless of the language.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, EnumIter, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Language {
+    /// Folds the special letters of every language below.
+    #[default]
+    Any,
+    Croatian,
+    Danish,
+    French,
+    German,
+    Icelandic,
+    Norwegian,
+    Polish,
+    Turkish,
+}
+
+impl Language {
+    /// Returns the special letters of the language with the letters they are folded to.
+    fn get_special_letters(&self) -> &'static [(char, &'static str)] {
+        match self {
+            Language::Any => &[],
+            Language::Croatian => &[('đ', "d"), ('Đ', "D")],
+            Language::Danish | Language::Norwegian => &[('ø', "o"), ('Ø', "O"), ('æ', "ae"), ('Æ', "AE")],
+            Language::French => &[('œ', "oe"), ('Œ', "OE"), ('æ', "ae"), ('Æ', "AE")],
+            Language::German => &[('ß', "ss"), ('ẞ', "SS")],
+            Language::Icelandic => &[('þ', "th"), ('Þ', "TH"), ('ð', "d"), ('Ð', "D"), ('æ', "ae"), ('Æ', "AE")],
+            Language::Polish => &[('ł', "l"), ('Ł', "L")],
+            Language::Turkish => &[('ı', "i")],
+        }
+    }
+
+    fn fold_special_letter(&self, c: char) -> Option<&'static str> {
+        let languages: Vec<Language> = if *self == Language::Any { Language::iter().collect() } else { vec![*self] };
+        languages.iter()
+            .flat_map(|language| language.get_special_letters())
+            .find(|(letter, _)| *letter == c)
+            .map(|(_, folded)| *folded)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttemptResult {
+    Correct,
+    /// Accepted with a penalty, as the attempt only differs from an answer by a few typos.
+    AlmostCorrect { attempt: String, expected: String },
+    /// Accepted, as the attempt only differs from an answer in its diacritics.
+    DiacriticsMismatch { expected: String, is_penalized: bool },
+    Incorrect,
+    /// Not answered within the time limit.
+    TimedOut { expected: String },
+}
+
+impl AttemptResult {
+    pub fn is_accepted(&self) -> bool {
+        !matches!(self, AttemptResult::Incorrect | AttemptResult::TimedOut { .. })
+    }
+
+    /// Returns the number of errors the outcome is counted as.
+    pub fn get_penalty(&self) -> u16 {
+        match self {
+            AttemptResult::Correct => 0,
+            AttemptResult::AlmostCorrect { .. } => ALMOST_CORRECT_PENALTY,
+            AttemptResult::DiacriticsMismatch { is_penalized: true, .. } => DIACRITICS_MISMATCH_PENALTY,
+            AttemptResult::DiacriticsMismatch { is_penalized: false, .. } => 0,
+            AttemptResult::Incorrect | AttemptResult::TimedOut { .. } => INCORRECT_ATTEMPT_PENALTY,
+        }
+    }
+}
+
+pub fn grade_attempt(attempt: &str, question: Rc<RefCell<Question>>, direction: Direction, settings: &GradingSettings) -> AttemptResult {
+    let question = question.borrow();
+    let Some((distance, closest_answer)) = get_closest_answer(attempt, &question, direction, &settings.ignored_symbols) else {
+        return AttemptResult::Incorrect;
+    };
+    if distance == 0 {
+        return AttemptResult::Correct;
+    }
+    if settings.diacritics_mode != DiacriticsMode::Strict {
+        let folded_attempt = fold_diacritics(&clean_string(attempt, &settings.ignored_symbols), settings.language);
+        let matching_answer = get_expected_answers(&question, direction).into_iter()
+            .find(|answer| fold_diacritics(&clean_string(answer, &settings.ignored_symbols), settings.language) == folded_attempt);
+        if let Some(answer) = matching_answer {
+            return AttemptResult::DiacriticsMismatch {
+                expected: answer,
+                is_penalized: settings.diacritics_mode == DiacriticsMode::PartialError,
+            };
+        }
+    }
+    if distance <= get_accepted_typo_count(&closest_answer, settings) {
+        return AttemptResult::AlmostCorrect { attempt: attempt.trim().to_string(), expected: closest_answer };
+    }
+    AttemptResult::Incorrect
+}
+
+/// Returns the accepted answer that is the most similar to the attempt, with their edit distance.
+pub fn get_closest_answer(attempt: &str, question: &Question, direction: Direction, ignored_symbols: &IgnoredSymbols) -> Option<(usize, String)> {
+    let cleaned_attempt = clean_string(attempt, ignored_symbols);
+    get_expected_answers(question, direction).into_iter()
+        .map(|answer| {
+            let distance = get_edit_distance(&cleaned_attempt, &clean_string(&answer, ignored_symbols));
+            (distance, answer)
+        })
+        .min_by_key(|(distance, _)| *distance)
+}
+
+/// A character of the attempt compared to the expected answer.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CharacterDiff {
+    Matching(char),
+    /// Expected, but not typed.
+    Missing(char),
+    /// Typed, but not expected.
+    Extra(char),
+    /// Typed instead of the expected character.
+    Wrong { typed: char, expected: char },
+}
+
+/// Aligns the attempt to the expected answer with the fewest edits and returns the differences character by character.
+/// Casing, extra whitespaces and punctuation are ignored, as they are ignored during grading too.
+pub fn get_character_diff(attempt: &str, expected: &str, ignored_symbols: &IgnoredSymbols) -> Vec<CharacterDiff> {
+    let typed: Vec<char> = clean_string(attempt, ignored_symbols).chars().collect();
+    let expected: Vec<char> = clean_string(expected, ignored_symbols).chars().collect();
+    let mut distances = vec![vec![0; expected.len() + 1]; typed.len() + 1];
+    (0..=typed.len()).for_each(|i| distances[i][0] = i);
+    (0..=expected.len()).for_each(|j| distances[0][j] = j);
+    for i in 1..=typed.len() {
+        for j in 1..=expected.len() {
+            let substitution_cost = if typed[i - 1] == expected[j - 1] { 0 } else { 1 };
+            distances[i][j] = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + substitution_cost);
+        }
+    }
+
+    // Walk back from the end, preferring matches and substitutions over insertions and deletions.
+    let mut diff = vec![];
+    let (mut i, mut j) = (typed.len(), expected.len());
+    while i > 0 || j > 0 {
+        if i > 0 && j > 0 {
+            let substitution_cost = if typed[i - 1] == expected[j - 1] { 0 } else { 1 };
+            if distances[i][j] == distances[i - 1][j - 1] + substitution_cost {
+                diff.push(if substitution_cost == 0 {
+                    CharacterDiff::Matching(typed[i - 1])
+                } else {
+                    CharacterDiff::Wrong { typed: typed[i - 1], expected: expected[j - 1] }
+                });
+                i -= 1;
+                j -= 1;
+                continue;
+            }
+        }
+        if i > 0 && distances[i][j] == distances[i - 1][j] + 1 {
+            diff.push(CharacterDiff::Extra(typed[i - 1]));
+            i -= 1;
+        } else {
+            diff.push(CharacterDiff::Missing(expected[j - 1]));
+            j -= 1;
+        }
+    }
+    diff.reverse();
+    diff
+}
+
+/// Defines how the answers are given during the practice.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, EnumMessage, Serialize, Deserialize)]
+pub enum AnswerMode {
+    /// The answer is typed in.
+    #[default]
+    #[strum(message="Typing")]
+    Typing,
+    /// The answer is picked from a few options.
+    #[strum(message="Choice")]
+    MultipleChoice,
+}
+
+impl AnswerMode {
+    pub fn next(&self) -> AnswerMode {
+        match self {
+            AnswerMode::Typing => AnswerMode::MultipleChoice,
+            AnswerMode::MultipleChoice => AnswerMode::Typing,
+        }
+    }
+}
+
+/// Returns one of the accepted answers mixed with distractors taken from the answers of the other questions,
+/// preferring the ones that start the same way or have a similar length.
+pub fn get_answer_options(
+    question: &Question,
+    direction: Direction,
+    other_questions: &[Rc<RefCell<Question>>],
+    option_count: usize,
+    ignored_symbols: &IgnoredSymbols,
+) -> Vec<String> {
+    let expected_answers = get_expected_answers(question, direction);
+    let Some(correct_answer) = expected_answers.choose(&mut rand::rng()).cloned() else { return vec![] };
+    let cleaned_expected_answers: HashSet<String> = expected_answers.iter()
+        .map(|answer| clean_string(answer, ignored_symbols))
+        .collect();
+    let mut distractors: Vec<String> = other_questions.iter()
+        .flat_map(|other_question| get_expected_answers(&other_question.borrow(), direction))
+        .filter(|distractor| !cleaned_expected_answers.contains(&clean_string(distractor, ignored_symbols)))
+        .collect();
+    // Shuffle first, so equally similar distractors are picked randomly.
+    distractors.shuffle(&mut rand::rng());
+    distractors.sort_by_key(|distractor| get_similarity_rank(distractor, &correct_answer, ignored_symbols));
+
+    let mut options = vec![correct_answer];
+    for distractor in distractors {
+        if options.len() >= option_count {
+            break;
+        }
+        if !options.iter().any(|option| clean_string(option, ignored_symbols) == clean_string(&distractor, ignored_symbols)) {
+            options.push(distractor);
+        }
+    }
+    options.shuffle(&mut rand::rng());
+    options
+}
+
+/// Ranks the most similar texts first: the longest common prefix, then the closest length.
+fn get_similarity_rank(text: &str, answer: &str, ignored_symbols: &IgnoredSymbols) -> (std::cmp::Reverse<usize>, usize) {
+    let text = clean_string(text, ignored_symbols);
+    let answer = clean_string(answer, ignored_symbols);
+    let common_prefix_length = text.chars().zip(answer.chars())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let length_difference = text.chars().count().abs_diff(answer.chars().count());
+    (std::cmp::Reverse(common_prefix_length), length_difference)
+}
+
+fn get_accepted_typo_count(answer: &str, settings: &GradingSettings) -> usize {
+    (clean_string(answer, &settings.ignored_symbols).chars().count() as f32 * settings.typo_tolerance.max(0.0)).floor() as usize
+}
+
+/// Removes the accents (e.g. "ę" to "e") and replaces the special letters of the language
+/// that have no decomposed form (e.g. Polish "ł" to "l" or German "ß" to "ss").
+pub fn fold_diacritics(text: &str, language: Language) -> String {
+    text.nfd()
+        .filter(|c| !is_combining_mark(*c))
+        .map(|c| language.fold_special_letter(c).map_or_else(|| c.to_string(), str::to_string))
+        .collect()
+}
+
+/// Returns the Damerau-Levenshtein (optimal string alignment) distance of the texts:
+/// the number of inserted, deleted, substituted or swapped adjacent characters.
+pub fn get_edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    (0..=a.len()).for_each(|i| distances[i][0] = i);
+    (0..=b.len()).for_each(|j| distances[0][j] = j);
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let substitution_cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            distances[i][j] = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + substitution_cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distances[i][j] = distances[i][j].min(distances[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    distances[a.len()][b.len()]
+}
+
+pub(crate) fn clean_string(response: &str, ignored_symbols: &IgnoredSymbols) -> String {
     let trimmed_lowercase = response
         .trim()
         .to_lowercase();
     let single_space = REGEX_MULTIPLE_SPACES.replace_all(&trimmed_lowercase, " ");
-    REGEX_SYMBOLS_TO_REMOVE.replace_all(single_space.as_ref(), "").to_string()
+    match &ignored_symbols.regex {
+        Some(regex) => regex.replace_all(single_space.as_ref(), "").to_string(),
+        None => single_space.to_string(),
+    }
+
+}
 
+/// Normalizes the text with the default ignored symbols, regardless of the config.
+pub(crate) fn get_normalized_key(text: &str) -> String {
+    clean_string(text, &DEFAULT_SYMBOLS_TO_REMOVE)
 }
 
 
@@ -67,10 +811,10 @@ mod tests {
     fn reveal_clue_for_for_single_answer() {
         // Given
         let question = Rc::new(RefCell::new(Question::new("location_1".to_string(), "question_1".to_string(), "answer_1".to_string())));
-        let expected = "a■s■e■_■".to_string();
+        let expected = vec!["a■s■e■_■".to_string()];
 
         // When
-        let actual = reveal_clue(question.clone());
+        let actual = reveal_clues(question.clone(), Direction::Forward);
 
         // Then
         assert_eq!(expected, actual);
@@ -82,14 +826,13 @@ mod tests {
         // Given
         let question = Rc::new(RefCell::new(Question::new("location_1".to_string(), "question_1".to_string(), "placeholder".to_string())));
         question.borrow_mut().answers  = HashSet::from(["answer_one".to_string(), "answer_two".to_string()]);
-        let expected = "a■s■e■_■n■".to_string();
-        let expected_variant = "a■s■e■_■w■".to_string();
+        let expected = vec!["a■s■e■_■n■".to_string(), "a■s■e■_■w■".to_string()];
 
         // When
-        let actual = reveal_clue(question);
+        let actual = reveal_clues(question, Direction::Forward);
 
         // Then
-        assert!(expected == actual || expected_variant == actual); // HashSet ordering can be random.
+        assert_eq!(expected, actual);
     }
 
 
@@ -97,10 +840,10 @@ mod tests {
     fn reveal_answer_for_single_answer() {
         // Given
         let question =  Rc::new(RefCell::new(Question::new("location_1".to_string(), "question_1".to_string(), "answer_1".to_string())));
-        let expected = "answer_1".to_string();
+        let expected = vec!["answer_1".to_string()];
 
         // When
-        let actual = reveal_answer(question);
+        let actual = reveal_answers(question, Direction::Forward);
 
         // Then
         assert_eq!(expected, actual);
@@ -112,14 +855,13 @@ mod tests {
         let question =  Rc::new(RefCell::new(Question::new("location_1".to_string(), "question_1".to_string(), "placeholder".to_string())));
         question.borrow_mut().answers = HashSet::from(["answer_one".to_string(), "answer_two".to_string()]);
 
-        let expected = "answer_one".to_string();
-        let expected_variant = "answer_two".to_string();
+        let expected = vec!["answer_one".to_string(), "answer_two".to_string()];
 
         // When
-        let actual = reveal_answer(question);
+        let actual = reveal_answers(question, Direction::Forward);
 
         // Then
-        assert!(expected == actual || expected_variant == actual); // HashSet ordering can be random.
+        assert_eq!(expected, actual);
 
     }
 
@@ -131,9 +873,9 @@ mod tests {
         let q3 = Rc::new(RefCell::new(Question::new(String::new(), String::from("q3"), String::new())));
         let q4 = Rc::new(RefCell::new(Question::new(String::new(), String::from("q3"), String::new())));
         // Expected order: q4, q2, q3
-        q2.borrow_mut().increment_error_count(5);
-        q3.borrow_mut().increment_error_count(1);
-        q4.borrow_mut().increment_error_count(10);
+        q2.borrow_mut().increment_error_count(Direction::Forward, 5);
+        q3.borrow_mut().increment_error_count(Direction::Forward, 1);
+        q4.borrow_mut().increment_error_count(Direction::Forward, 10);
 
         let limit = 3;
 
@@ -141,39 +883,465 @@ mod tests {
         let expected = vec![questions[3].clone(), questions[1].clone(), questions[2].clone()]; // Will drop q1, due to the limit.
 
         // When
-        let actual = get_hardest_questions_in_round(&questions, limit);
+        let actual = get_hardest_questions_in_round(&questions, limit, &[Direction::Forward]);
 
         // Then
         assert_eq!(actual, expected);
     }
 
     #[test]
-    fn is_attempt_successful_matches_answer() {
+    fn hardest_questions_with_equal_round_errors_are_ranked_by_previous_sessions() {
+        // Given
+        let q1 = Rc::new(RefCell::new(Question::new(String::new(), String::from("q1"), String::new())));
+        let q2 = Rc::new(RefCell::new(Question::new(String::new(), String::from("q2"), String::new())));
+        q2.borrow_mut().increment_error_count(Direction::Forward, 3);
+        q2.borrow_mut().reset_round_stats();
+
+        let questions = vec![q1.clone(), q2.clone()];
+
+        // When
+        let actual = get_hardest_questions_in_round(&questions, 1, &[Direction::Forward]);
+
+        // Then
+        assert_eq!(actual, vec![q2]);
+    }
+
+    #[test]
+    fn slow_answers_make_questions_harder() {
+        // Given
+        let fast = Rc::new(RefCell::new(Question::new(String::new(), String::from("fast"), String::new())));
+        let slow = Rc::new(RefCell::new(Question::new(String::new(), String::from("slow"), String::new())));
+        let slow_but_correct = Rc::new(RefCell::new(Question::new(String::new(), String::from("slow but correct"), String::new())));
+        fast.borrow_mut().increment_error_count(Direction::Forward, 2);
+        fast.borrow_mut().record_response_time(Direction::Forward, Duration::from_secs(2));
+        slow.borrow_mut().increment_error_count(Direction::Forward, 2);
+        slow.borrow_mut().record_response_time(Direction::Forward, Duration::from_secs(12));
+        slow_but_correct.borrow_mut().record_response_time(Direction::Forward, Duration::from_secs(30));
+        let questions = vec![fast.clone(), slow.clone(), slow_but_correct.clone()];
+
+        // When
+        let actual = get_hardest_questions_in_round(&questions, 3, &[Direction::Forward]);
+
+        // Then
+        assert_eq!(get_difficulty_score(&slow.borrow(), &[Direction::Forward]), (4, 4));
+        assert_eq!(actual, vec![slow_but_correct, slow, fast]);
+    }
+
+    #[test]
+    fn median_response_time_is_not_skewed_by_a_single_slow_answer() {
+        // Given
+        let durations = [1, 2, 3, 60].map(Duration::from_secs);
+
+        // When
+        let median = get_median_duration(&durations);
+        let mean = get_mean_duration(&durations);
+
+        // Then
+        assert_eq!(median, Some(Duration::from_millis(2500)));
+        assert_eq!(mean, Some(Duration::from_millis(16500)));
+        assert_eq!(get_median_duration(&[]), None);
+    }
+
+    #[test]
+    fn review_schedule_intervals_grow_with_successful_reviews() {
+        // Given
+        let mut schedule = ReviewSchedule::default();
+        let day = 20_000;
+        let timestamp = day * SECONDS_PER_DAY;
+
+        // When
+        schedule.review(5, timestamp);
+        let first_interval = schedule.interval_days;
+        schedule.review(5, timestamp);
+        let second_interval = schedule.interval_days;
+        schedule.review(5, timestamp);
+
+        // Then
+        assert_eq!(first_interval, 1);
+        assert_eq!(second_interval, 6);
+        assert_eq!(schedule.interval_days, 16); // 6 * 2.7
+        assert_eq!(schedule.due_day, Some(day + 16));
+        assert_eq!(schedule.last_review_timestamp, Some(timestamp));
+    }
+
+    #[test]
+    fn review_schedule_is_reset_on_failed_recall() {
+        // Given
+        let mut schedule = ReviewSchedule::default();
+        schedule.review(5, 0);
+        schedule.review(5, 0);
+
+        // When
+        schedule.review(0, 0);
+
+        // Then
+        assert_eq!(schedule.repetitions, 0);
+        assert_eq!(schedule.interval_days, 1);
+        assert!(schedule.is_due(0), "Failed questions are repeated on the same day");
+        assert!(schedule.ease_factor >= MIN_EASE_FACTOR);
+        assert!(schedule.ease_factor < DEFAULT_EASE_FACTOR);
+    }
+
+    #[test]
+    fn due_questions_are_ordered_by_urgency() {
+        // Given
+        let today = 100;
+        let new = Rc::new(RefCell::new(Question::new(String::new(), String::from("new"), String::new())));
+        let overdue = Rc::new(RefCell::new(Question::new(String::new(), String::from("overdue"), String::new())));
+        let due_today = Rc::new(RefCell::new(Question::new(String::new(), String::from("due_today"), String::new())));
+        let not_due = Rc::new(RefCell::new(Question::new(String::new(), String::from("not_due"), String::new())));
+        overdue.borrow_mut().get_schedule_mut(Direction::Forward).due_day = Some(today - 3);
+        due_today.borrow_mut().get_schedule_mut(Direction::Forward).due_day = Some(today);
+        not_due.borrow_mut().get_schedule_mut(Direction::Forward).due_day = Some(today + 1);
+
+        let questions = vec![new.clone(), overdue.clone(), due_today.clone(), not_due];
+
+        // When
+        let actual = get_due_questions(&questions, today, &[Direction::Forward]);
+
+        // Then
+        assert_eq!(actual, vec![overdue, due_today, new]);
+    }
+
+    #[test]
+    fn grade_attempt_matches_answer() {
         // Given
         let question = Rc::new(RefCell::new(Question::new(String::new(), String::from("q1"), String::new())));
         question.borrow_mut().answers = HashSet::from(["answer one".to_string(), "answer two".to_string()]);
         let attempt = "answer one";
 
         // When
-        let actual = is_attempt_successful(attempt, question);
+        let actual = grade_attempt(attempt, question, Direction::Forward, &GradingSettings::default());
 
         //Then
-        assert_eq!(actual, true);
+        assert_eq!(actual, AttemptResult::Correct);
     }
 
 
     #[test]
-    fn is_attempt_successful_no_answer_to_match() {
+    fn grade_attempt_no_answer_to_match() {
         // Given
         let question = Rc::new(RefCell::new(Question::new(String::new(), String::from("q1"), String::new())));
         question.borrow_mut().answers = HashSet::from(["answer one".to_string(), "answer two".to_string()]);
         let attempt = "something else";
 
         // When
-        let actual = is_attempt_successful(attempt, question);
+        let actual = grade_attempt(attempt, question, Direction::Forward, &GradingSettings::default());
+
+        //Then
+        assert_eq!(actual, AttemptResult::Incorrect);
+    }
+
+    #[test]
+    fn grade_attempt_with_typos_is_almost_correct() {
+        // Given
+        let question = Rc::new(RefCell::new(Question::new(String::new(), String::from("q1"), String::new())));
+        question.borrow_mut().answers = HashSet::from(["answer one".to_string(), "other".to_string()]);
+        let attempt = "nasewr one"; // Two swapped characters within 10 characters
+
+        // When
+        let actual = grade_attempt(attempt, question, Direction::Forward, &GradingSettings::default());
+
+        //Then
+        assert_eq!(actual, AttemptResult::AlmostCorrect {
+            attempt: "nasewr one".to_string(),
+            expected: "answer one".to_string(),
+        });
+    }
+
+    #[test]
+    fn grade_attempt_without_typo_tolerance_requires_exact_match() {
+        // Given
+        let question = Rc::new(RefCell::new(Question::new(String::new(), String::from("q1"), String::from("answer one"))));
+        let settings = GradingSettings { typo_tolerance: 0.0, ..GradingSettings::default() };
+
+        // When
+        let actual = grade_attempt("answer onr", question, Direction::Forward, &settings);
+
+        //Then
+        assert_eq!(actual, AttemptResult::Incorrect);
+    }
+
+    #[test]
+    fn grade_attempt_without_diacritics_depends_on_the_mode() {
+        // Given
+        let question = Rc::new(RefCell::new(Question::new(String::new(), String::from("5"), String::from("pięć"))));
+        let get_settings = |diacritics_mode| GradingSettings { typo_tolerance: 0.0, diacritics_mode, ..GradingSettings::default() };
+
+        // When
+        let strict = grade_attempt("piec", question.clone(), Direction::Forward, &get_settings(DiacriticsMode::Strict));
+        let warn = grade_attempt("piec", question.clone(), Direction::Forward, &get_settings(DiacriticsMode::Warn));
+        let partial_error = grade_attempt("piec", question.clone(), Direction::Forward, &get_settings(DiacriticsMode::PartialError));
+        let exact = grade_attempt("pięć", question, Direction::Forward, &get_settings(DiacriticsMode::Warn));
 
         //Then
-        assert_eq!(actual, false);
+        assert_eq!(strict, AttemptResult::Incorrect);
+        assert_eq!(warn, AttemptResult::DiacriticsMismatch { expected: "pięć".to_string(), is_penalized: false });
+        assert_eq!(warn.get_penalty(), 0);
+        assert_eq!(partial_error, AttemptResult::DiacriticsMismatch { expected: "pięć".to_string(), is_penalized: true });
+        assert_eq!(partial_error.get_penalty(), DIACRITICS_MISMATCH_PENALTY);
+        assert_eq!(exact, AttemptResult::Correct);
+    }
+
+    #[test]
+    fn fold_diacritics_handles_letters_without_decomposition() {
+        assert_eq!(fold_diacritics("źdźbło", Language::Any), "zdzblo");
+        assert_eq!(fold_diacritics("Straße", Language::Any), "Strasse");
+        assert_eq!(fold_diacritics("smørrebrød", Language::Any), "smorrebrod");
+        assert_eq!(fold_diacritics("déjà vu", Language::Any), "deja vu");
+    }
+
+    #[test]
+    fn fold_diacritics_only_replaces_the_special_letters_of_the_language() {
+        assert_eq!(fold_diacritics("źdźbło", Language::Polish), "zdzblo");
+        assert_eq!(fold_diacritics("Straße", Language::Polish), "Straße");
+        assert_eq!(fold_diacritics("Straße", Language::German), "Strasse");
+        assert_eq!(fold_diacritics("smørrebrød", Language::Danish), "smorrebrod");
+        assert_eq!(fold_diacritics("smørrebrød", Language::German), "smørrebrød");
+        assert_eq!(fold_diacritics("déjà vu", Language::German), "deja vu");
+    }
+
+    #[test]
+    fn grade_attempt_matching_one_of_the_answers_exactly_is_correct() {
+        // Given
+        let question = Rc::new(RefCell::new(Question::new(String::new(), String::from("q1"), String::new())));
+        question.borrow_mut().answers = HashSet::from(["się".to_string(), "sie".to_string()]);
+        let settings = GradingSettings { diacritics_mode: DiacriticsMode::Warn, ..GradingSettings::default() };
+
+        // When
+        let actual = grade_attempt("sie", question, Direction::Forward, &settings);
+
+        //Then
+        assert_eq!(actual, AttemptResult::Correct);
+    }
+
+    #[test]
+    fn character_diff_shows_missing_extra_and_wrong_characters() {
+        // Given
+        let attempt = "Pjes!x";
+        let expected = "pies";
+
+        // When
+        let actual = get_character_diff(attempt, expected, &IgnoredSymbols::default());
+
+        // Then
+        assert_eq!(actual, vec![
+            CharacterDiff::Matching('p'),
+            CharacterDiff::Wrong { typed: 'j', expected: 'i' },
+            CharacterDiff::Matching('e'),
+            CharacterDiff::Matching('s'),
+            CharacterDiff::Extra('x'),
+        ]);
+        assert_eq!(get_character_diff("pis", "pies", &IgnoredSymbols::default())[2], CharacterDiff::Missing('e'));
+    }
+
+    #[test]
+    fn answer_options_prefer_similar_distractors() {
+        // Given
+        let question = Question::new(String::new(), String::from("dog"), String::from("pies"));
+        let other_questions = [("puppy", "piesek"), ("cat", "kot"), ("bird", "ptak"), ("horse", "koń"), ("elephant", "słoń")]
+            .map(|(question, answer)| Rc::new(RefCell::new(Question::new(String::new(), question.to_string(), answer.to_string()))));
+
+        // When
+        let mut actual = get_answer_options(&question, Direction::Forward, &other_questions, ANSWER_OPTION_COUNT, &IgnoredSymbols::default());
+
+        // Then
+        actual.sort();
+        assert_eq!(actual, vec!["pies", "piesek", "ptak", "słoń"]);
+    }
+
+    #[test]
+    fn answer_options_do_not_contain_accepted_answers_as_distractors() {
+        // Given
+        let mut question = Question::new(String::new(), String::from("dog"), String::from("pies"));
+        question.answers.insert(String::from("psa"));
+        let other_questions = [("dog", "Psa!"), ("cat", "kot"), ("kitten", "kot")]
+            .map(|(question, answer)| Rc::new(RefCell::new(Question::new(String::new(), question.to_string(), answer.to_string()))));
+
+        // When
+        let actual = get_answer_options(&question, Direction::Forward, &other_questions, ANSWER_OPTION_COUNT, &IgnoredSymbols::default());
+
+        // Then
+        assert_eq!(actual.len(), 2, "Only the distinct distractors are added");
+        assert!(actual.contains(&String::from("kot")));
+        assert!(actual.iter().any(|option| option == "pies" || option == "psa"));
+    }
+
+    #[test]
+    fn edit_distance_counts_swapped_characters_as_one_edit() {
+        assert_eq!(get_edit_distance("pies", "pies"), 0);
+        assert_eq!(get_edit_distance("pies", "peis"), 1);
+        assert_eq!(get_edit_distance("pies", "pie"), 1);
+        assert_eq!(get_edit_distance("pies", "psy"), 3);
+        assert_eq!(get_edit_distance("", "psa"), 3);
+    }
+
+    #[test]
+    fn reverse_attempt_is_checked_against_the_question() {
+        // Given
+        let question = Rc::new(RefCell::new(Question::new(String::new(), String::from("dog"), String::from("pies"))));
+
+        // When
+        let prompt = get_prompt(&question.borrow(), Direction::Reverse);
+        let question_result = grade_attempt("dog", question.clone(), Direction::Reverse, &GradingSettings::default());
+        let answer_result = grade_attempt("pies", question, Direction::Reverse, &GradingSettings::default());
+
+        // Then
+        assert_eq!(prompt, "pies");
+        assert_eq!(question_result, AttemptResult::Correct);
+        assert_eq!(answer_result, AttemptResult::Incorrect);
+    }
+
+    #[test]
+    fn due_questions_only_consider_the_given_directions() {
+        // Given
+        let today = 100;
+        let question = Rc::new(RefCell::new(Question::new(String::new(), String::from("q1"), String::new())));
+        question.borrow_mut().get_schedule_mut(Direction::Forward).due_day = Some(today + 1);
+        let questions = vec![question.clone()];
+
+        // When
+        let due_forward = get_due_questions(&questions, today, &[Direction::Forward]);
+        let due_any = get_due_questions(&questions, today, &[Direction::Forward, Direction::Reverse]);
+
+        // Then
+        assert!(due_forward.is_empty());
+        assert_eq!(due_any, vec![question]);
+    }
+
+    #[test]
+    fn leitner_boxes_are_practiced_with_decreasing_frequency() {
+        // Given
+        let box_1 = Rc::new(RefCell::new(Question::new(String::new(), String::from("q1"), String::new())));
+        let box_2 = Rc::new(RefCell::new(Question::new(String::new(), String::from("q2"), String::new())));
+        box_2.borrow_mut().get_leitner_mut(Direction::Forward).review(true);
+        let questions = vec![box_1.clone(), box_2.clone()];
+        let directions = [Direction::Forward];
+
+        // When
+        let due_in_first_session = start_leitner_session(&questions, &directions);
+        let due_in_second_session = start_leitner_session(&questions, &directions);
+
+        // Then
+        assert_eq!(due_in_first_session, vec![box_1.clone()], "Box 1 is practiced in every session");
+        assert_eq!(due_in_second_session, vec![box_1, box_2], "Box 2 is practiced in every second session");
+    }
+
+    #[test]
+    fn leitner_sessions_without_due_questions_are_skipped() {
+        // Given
+        let question = Rc::new(RefCell::new(Question::new(String::new(), String::from("q1"), String::new())));
+        (0..2).for_each(|_| question.borrow_mut().get_leitner_mut(Direction::Forward).review(true));
+        let questions = vec![question.clone()];
+
+        // When
+        let due_questions = start_leitner_session(&questions, &[Direction::Forward]);
+
+        // Then
+        assert_eq!(due_questions, vec![question.clone()], "Box 3 is due in the fourth session");
+        assert_eq!(question.borrow().get_leitner(Direction::Forward), &LeitnerState { box_number: 3, sessions_waited: 4 });
+    }
+
+    #[test]
+    fn leitner_state_is_unchanged_without_due_questions() {
+        // Given
+        let question = Rc::new(RefCell::new(Question::new(String::new(), String::from("q1"), String::new())));
+        question.borrow_mut().get_leitner_mut(Direction::Forward).review(true);
+        let questions = vec![question.clone()];
+        let expected = question.borrow().get_leitner(Direction::Forward).clone();
+
+        // When
+        let due_in_first_call = start_leitner_session(&questions, &[]);
+        let due_in_second_call = start_leitner_session(&questions, &[]);
+
+        // Then
+        assert!(due_in_first_call.is_empty() && due_in_second_call.is_empty());
+        assert!(start_leitner_session(&[], &[Direction::Forward]).is_empty());
+        assert_eq!(question.borrow().get_leitner(Direction::Forward), &expected);
+    }
+
+    #[test]
+    fn leitner_wrong_answer_demotes_to_the_first_box() {
+        // Given
+        let mut leitner = LeitnerState::default();
+        (0..10).for_each(|_| leitner.review(true));
+        let promoted_box = leitner.box_number;
+
+        // When
+        leitner.review(false);
+
+        // Then
+        assert_eq!(promoted_box, LEITNER_BOX_COUNT);
+        assert_eq!(leitner.box_number, 1);
+        assert_eq!(LeitnerState { box_number: 3, sessions_waited: 0 }.get_session_interval(), 4);
+    }
+
+    #[test]
+    fn leitner_box_counts_include_every_direction() {
+        // Given
+        let q1 = Rc::new(RefCell::new(Question::new(String::new(), String::from("q1"), String::new())));
+        let q2 = Rc::new(RefCell::new(Question::new(String::new(), String::from("q2"), String::new())));
+        q2.borrow_mut().get_leitner_mut(Direction::Reverse).review(true);
+
+        // When
+        let actual = get_leitner_box_counts(&[q1, q2], &[Direction::Forward, Direction::Reverse]);
+
+        // Then
+        assert_eq!(actual, [3, 1, 0, 0, 0]);
+    }
+
+    #[test]
+    fn mastered_questions_retire_after_correct_answers_in_a_row() {
+        // Given
+        let q1 = Rc::new(RefCell::new(Question::new(String::new(), String::from("q1"), String::new())));
+        let q2 = Rc::new(RefCell::new(Question::new(String::new(), String::from("q2"), String::new())));
+        let mut round = MasteryRound::new(vec![q1.clone(), q2.clone()], 2);
+
+        // When
+        round.answer_current_question(true); // q1: 1
+        round.answer_current_question(true); // q2: 1
+        round.answer_current_question(false); // q1: 0
+        round.answer_current_question(true); // q2: 2, mastered
+
+        // Then
+        assert_eq!(round.get_questions(), vec![q1]);
+        assert_eq!(round.get_mastered_count(), 1);
+        assert!(!round.is_complete());
+        round.answer_current_question(true);
+        round.answer_current_question(true);
+        assert!(round.is_complete());
+    }
+
+    #[test]
+    fn wrong_answers_are_asked_again_a_few_questions_later() {
+        // Given
+        let questions: Vec<Rc<RefCell<Question>>> = (1..=6)
+            .map(|i| Rc::new(RefCell::new(Question::new(String::new(), format!("q{i}"), String::new()))))
+            .collect();
+        let mut round = MasteryRound::new(questions.clone(), 1);
+
+        // When
+        round.answer_current_question(false);
+
+        // Then
+        let actual = round.get_questions();
+        assert_eq!(actual.len(), 6);
+        assert_eq!(actual[MASTERY_REQUEUE_DISTANCE], questions[0]);
+        assert_eq!(actual[0], questions[1]);
+    }
+
+    #[test]
+    fn review_quality_follows_the_configured_reveal_penalties() {
+        // Given
+        let reveal_penalties = RevealPenalties { clue_reveal: 3, answer_reveal: 6 };
+
+        // When
+        let qualities: Vec<u8> = [0, 1, 2, 3, 5, 6].into_iter()
+            .map(|penalty| get_review_quality(penalty, &reveal_penalties))
+            .collect();
+
+        // Then
+        assert_eq!(qualities, vec![5, 4, 3, 2, 2, 0]);
     }
 
     #[test]
@@ -183,10 +1351,37 @@ mod tests {
         let expected = "correct answer".to_string();
 
         // When
-        let actual = clean_string(&input);
+        let actual = clean_string(&input, &IgnoredSymbols::default());
 
         // Then
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn configured_ignored_symbols_are_used_for_grading() {
+        // Given
+        let question = Rc::new(RefCell::new(Question::new(String::new(), String::from("smile"), String::from(":-)"))));
+        let get_settings = |symbols| GradingSettings { typo_tolerance: 0.0, ignored_symbols: IgnoredSymbols::new(symbols).unwrap(), ..GradingSettings::default() };
+
+        // When
+        let with_default_symbols = grade_attempt("-)", question.clone(), Direction::Forward, &get_settings(DEFAULT_IGNORED_SYMBOLS));
+        let without_symbols = grade_attempt("-)", question, Direction::Forward, &get_settings(""));
+
+        // Then
+        assert_eq!(with_default_symbols, AttemptResult::Correct);
+        assert_eq!(without_symbols, AttemptResult::Incorrect);
+    }
+
+    #[test]
+    fn normalized_keys_do_not_depend_on_the_configured_symbols() {
+        // Given
+        let input = "Dog?";
+
+        // When
+        let actual = get_normalized_key(input);
+
+        // Then
+        assert_eq!(actual, "dog");
+    }
+
 }
\ No newline at end of file
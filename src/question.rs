@@ -1,57 +1,200 @@
-use std::{collections::HashSet, hash::Hash};
+use std::{collections::{BTreeMap, BTreeSet, HashSet}, hash::Hash, time::Duration};
+use serde::{Deserialize, Serialize};
+use crate::practice::{Direction, LeitnerState, ReviewSchedule};
+
+/// The number of the most recent response times kept for each direction.
+const RESPONSE_TIME_HISTORY_LIMIT: usize = 20;
 
 #[derive(Debug, Clone)]
 pub struct Question {
     pub question: String,
     pub answers: HashSet<String>,
     pub locations: HashSet<String>,
+    /// The name of the group shown in the group list.
+    pub group: String,
+    /// The identity of the group the stats are saved for, see `QuestionGroup::id`.
+    pub group_id: String,
+    /// The files each answer was read from.
+    answer_locations: BTreeMap<String, BTreeSet<String>>,
+
+    stats: QuestionStats,
+}
 
+/// Practice stats of a question, tracked separately for each practice direction.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QuestionStats {
+    // Flattened to stay compatible with the stats saved before reverse practice was introduced.
+    #[serde(flatten)]
+    pub forward: DirectionStats,
+    pub reverse: DirectionStats,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DirectionStats {
+    #[serde(skip)]
     correct_count_round: u16,
+    #[serde(skip)]
     error_count_round: u16,
+    /// Time from displaying the question to each attempt in the round.
+    #[serde(skip)]
+    response_times_round: Vec<Duration>,
     correct_count_sum: u16,
     error_count_sum: u16,
+    /// The most recent response times in milliseconds, the oldest first.
+    response_times_ms: Vec<u32>,
+    pub schedule: ReviewSchedule,
+    pub leitner: LeitnerState,
 }
 
 impl Question {
+    /// Returns a question with a single answer, a shorthand used by the tests.
+    #[cfg(test)]
     pub fn new(location: String, question: String, answer: String) -> Question {
+        Question::with_answers(location, question, HashSet::from([answer]))
+    }
+
+    /// Returns a question with every accepted answer read from the same location.
+    pub fn with_answers(location: String, question: String, answers: HashSet<String>) -> Question {
         Question {
             question,
+            answer_locations: answers.iter()
+                .map(|answer| (answer.clone(), BTreeSet::from([location.clone()])))
+                .collect(),
             locations: HashSet::from([location]),
-            answers: HashSet::from([answer]),
+            answers,
+            group: String::new(),
+            group_id: String::new(),
 
-            correct_count_round: 0,
-            error_count_round: 0,
-            correct_count_sum: 0,
-            error_count_sum: 0,
+            stats: QuestionStats::default(),
         }
     }
 }
 
 impl Question {
-    pub fn increment_correct_count(&mut self, amount: u16) {
-        self.correct_count_round += amount;
-        self.correct_count_sum += amount;
+
+    /// Adds the answers and locations of the same question read from another line or file of the group.
+    pub fn merge_answers_from(&mut self, other: &Question) {
+        self.answers.extend(other.answers.iter().cloned());
+        self.locations.extend(other.locations.iter().cloned());
+        for (answer, locations) in &other.answer_locations {
+            self.answer_locations.entry(answer.clone())
+                .or_default()
+                .extend(locations.iter().cloned());
+        }
     }
 
-    pub fn increment_error_count(&mut self, amount: u16) {
-        self.error_count_round += amount;
-        self.error_count_sum += amount;
+    /// Returns the sorted answers by the files they were read from, under an empty location if it's unknown.
+    pub fn get_answers_by_locations(&self) -> BTreeMap<String, BTreeSet<String>> {
+        let mut answers_by_locations: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        for answer in &self.answers {
+            let locations = self.answer_locations.get(answer)
+                .filter(|locations| !locations.is_empty())
+                .cloned()
+                .unwrap_or_else(|| BTreeSet::from([String::new()]));
+            for location in locations {
+                answers_by_locations.entry(location).or_default().insert(answer.clone());
+            }
+        }
+        answers_by_locations
+    }
+
+    pub fn increment_correct_count(&mut self, direction: Direction, amount: u16) {
+        let stats = self.get_direction_stats_mut(direction);
+        stats.correct_count_round = stats.correct_count_round.saturating_add(amount);
+        stats.correct_count_sum = stats.correct_count_sum.saturating_add(amount);
+    }
+
+    pub fn increment_error_count(&mut self, direction: Direction, amount: u16) {
+        let stats = self.get_direction_stats_mut(direction);
+        stats.error_count_round = stats.error_count_round.saturating_add(amount);
+        stats.error_count_sum = stats.error_count_sum.saturating_add(amount);
     }
 
     pub fn reset_round_stats(&mut self) {
-        self.correct_count_round = 0;
-        self.error_count_round = 0;
+        for stats in [&mut self.stats.forward, &mut self.stats.reverse] {
+            stats.correct_count_round = 0;
+            stats.error_count_round = 0;
+            stats.response_times_round.clear();
+        }
+    }
+
+    pub fn record_response_time(&mut self, direction: Direction, response_time: Duration) {
+        let stats = self.get_direction_stats_mut(direction);
+        stats.response_times_round.push(response_time);
+        stats.response_times_ms.push(response_time.as_millis().min(u32::MAX as u128) as u32);
+        if stats.response_times_ms.len() > RESPONSE_TIME_HISTORY_LIMIT {
+            stats.response_times_ms.remove(0);
+        }
+    }
+
+    /// Returns the most recent response times, including the ones from previous sessions.
+    pub fn get_response_times(&self, direction: Direction) -> Vec<Duration> {
+        self.get_direction_stats(direction).response_times_ms.iter()
+            .map(|millis| Duration::from_millis(*millis as u64))
+            .collect()
+    }
+
+    pub fn get_response_times_for_round(&self, direction: Direction) -> &[Duration] {
+        &self.get_direction_stats(direction).response_times_round
+    }
+
+    pub fn get_error_count_for_round(&self, direction: Direction) -> u16 {
+        self.get_direction_stats(direction).error_count_round
+    }
+
+    pub fn get_error_count_sum(&self, direction: Direction) -> u16 {
+        self.get_direction_stats(direction).error_count_sum
+    }
+
+    pub fn get_schedule(&self, direction: Direction) -> &ReviewSchedule {
+        &self.get_direction_stats(direction).schedule
+    }
+
+    pub fn get_schedule_mut(&mut self, direction: Direction) -> &mut ReviewSchedule {
+        &mut self.get_direction_stats_mut(direction).schedule
+    }
+
+    pub fn get_leitner(&self, direction: Direction) -> &LeitnerState {
+        &self.get_direction_stats(direction).leitner
     }
 
-    pub fn get_error_count_for_round(&self) -> u16 {
-        self.error_count_round
+    pub fn get_leitner_mut(&mut self, direction: Direction) -> &mut LeitnerState {
+        &mut self.get_direction_stats_mut(direction).leitner
+    }
+
+    /// Restores the all-time stats persisted from previous sessions.
+    pub fn load_stats(&mut self, stats: &QuestionStats) {
+        self.stats = stats.clone();
+        self.reset_round_stats();
+    }
+
+    pub fn get_stats(&self) -> QuestionStats {
+        self.stats.clone()
+    }
+
+    fn get_direction_stats(&self, direction: Direction) -> &DirectionStats {
+        match direction {
+            Direction::Forward => &self.stats.forward,
+            Direction::Reverse => &self.stats.reverse,
+        }
+    }
+
+    fn get_direction_stats_mut(&mut self, direction: Direction) -> &mut DirectionStats {
+        match direction {
+            Direction::Forward => &mut self.stats.forward,
+            Direction::Reverse => &mut self.stats.reverse,
+        }
     }
 
 }
 
+// Questions with the same text are different questions in different groups, e.g. homographs of two languages.
+// The groups are compared by their identity, so a question stays the same however the app is started.
 impl PartialEq for Question {
     fn eq(&self, other: &Self) -> bool {
-        self.question == other.question
+        self.group_id == other.group_id && self.question == other.question
     }
 }
 
@@ -59,6 +202,7 @@ impl Eq for Question {}
 
 impl Hash for Question {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.group_id.hash(state);
         self.question.hash(state);
     }
 }
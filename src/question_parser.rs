@@ -1,18 +1,85 @@
 use regex::Regex;
+use serde::Serialize;
+use crate::custom_deck::DeckEntry;
+use crate::practice;
+use crate::practice::DiacriticsMode;
 use crate::question::Question;
-use std::{env, fs::{self}, path::PathBuf, sync::LazyLock};
+use crate::stats_store::StatsStore;
+use std::{env, fmt, fs::{self}, io, path::{Path, PathBuf}, sync::LazyLock};
 use std::cell::RefCell;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::rc::Rc;
-use walkdir::{DirEntry, Error, WalkDir};
-
+use walkdir::{DirEntry, WalkDir};
+
+/// Separates the accepted answer variants within a line, e.g. `dog | pies ; psa`
+pub const ANSWER_SEPARATOR: char = ';';
+/// Escapes the answer separator to keep it as a literal character of the answer, e.g. `smiley | \;)`
+const ANSWER_SEPARATOR_ESCAPE: char = '\\';
+/// Separates the folders in group names, e.g. `polish/lesson1/numbers`
+pub const GROUP_PATH_SEPARATOR: char = '/';
+/// Opts in to merging the files with the same name into one group, regardless of their folders.
+pub const MERGE_SAME_NAMES_FLAG: &str = "--merge-same-names";
 static MULTIPLE_WHITESPACES_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\s+").unwrap());
 
 #[derive(Debug, Default, Clone, PartialEq)]
 pub struct QuestionGroup {
+    /// Identifies the group in the saved stats, decks and sessions, regardless of how the app was started:
+    /// the canonical paths of its files, or the name of a custom deck.
+    pub id: String,
     pub paths: Vec<PathBuf>,
     pub questions: Vec<Rc<RefCell<Question>>>,
     pub is_active: bool,
+    /// Overrides the global diacritics mode for the questions of the group.
+    pub diacritics_mode: Option<DiacriticsMode>,
+    /// The picked questions of a custom deck, `None` for groups read from files.
+    pub deck: Option<BTreeSet<DeckEntry>>,
+}
+
+/// Defines how the group names are derived from the question files.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum GroupNaming {
+    /// The path of the file relative to the input path, e.g. `polish/lesson1/numbers`,
+    /// prefixed with the name of the input path if there are multiple ones.
+    #[default]
+    ByPath,
+    /// The file name only, so the files with the same name are merged into one group.
+    ByFileName,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// A problem found while reading the question files, e.g. a malformed line that was skipped.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ParseDiagnostic {
+    pub file: String,
+    /// 1-based line number, `None` if the problem concerns the whole file.
+    pub line: Option<usize>,
+    /// 1-based column (in characters), `None` if the problem concerns the whole line.
+    pub column: Option<usize>,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl fmt::Display for ParseDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{}", self.file)?;
+        if let Some(line) = self.line {
+            write!(f, ":{}", line)?;
+        }
+        if let Some(column) = self.column {
+            write!(f, ":{}", column)?;
+        }
+        write!(f, " {}: {}", severity, self.message)
+    }
 }
 
 /// Returns the paths passed in as commandline arguments or the current working directory if there was none
@@ -23,108 +90,197 @@ pub fn get_paths_from(args: Vec<String>) -> Vec<PathBuf> {
     if paths.is_empty() { vec![env::current_dir().unwrap()] } else { paths }
 }
 
-pub fn collect_groups_from_multiple_paths(paths: Vec<PathBuf>) -> (BTreeMap<String, QuestionGroup>, BTreeMap<usize, String>) {
-    let questions_by_groups = paths.into_iter()
-        .flat_map(get_all_files_under_path)
-        .filter(|dir_entry| dir_entry.file_name().to_owned()
+/// Returns the group naming selected by the commandline arguments and the remaining arguments.
+pub fn get_group_naming_from(args: Vec<String>) -> (GroupNaming, Vec<String>) {
+    let (flags, other_args): (Vec<String>, Vec<String>) = args.into_iter()
+        .partition(|arg| arg == MERGE_SAME_NAMES_FLAG);
+    let group_naming = if flags.is_empty() { GroupNaming::ByPath } else { GroupNaming::ByFileName };
+    (group_naming, other_args)
+}
+
+pub fn collect_groups_from_multiple_paths(
+    paths: Vec<PathBuf>,
+    group_naming: GroupNaming,
+    diagnostics: &mut Vec<ParseDiagnostic>,
+) -> BTreeMap<String, QuestionGroup> {
+    // Files under different input paths are told apart by the names of the input paths.
+    let is_prefixed_with_input_path = paths.len() > 1;
+    paths.into_iter()
+        .flat_map(|path| get_all_files_under_path(path.clone(), diagnostics).into_iter()
+            .map(move |dir_entry| (path.clone(), dir_entry)))
+        .filter(|(_, dir_entry)| dir_entry.file_name().to_owned()
             .into_string()
             .unwrap_or("".to_string())
             .ends_with(".sll")
         )
+        .map(|(input_path, dir_entry)| {
+            let group_name = match group_naming {
+                GroupNaming::ByPath => get_group_name_from_path(&input_path, dir_entry.path(), is_prefixed_with_input_path),
+                GroupNaming::ByFileName => get_group_name_from_file_name(dir_entry.path()),
+            };
+            (group_name, dir_entry)
+        })
         // group Questions from files with matching names
-        .fold(BTreeMap::new(), merge_paths_from_matching_groups);
+        .fold(BTreeMap::new(), merge_paths_from_matching_groups)
+        .into_iter()
+        .map(|(group_name, group)| (group_name, QuestionGroup { id: get_paths_key(&group.paths), ..group }))
+        .collect()
+}
 
-    let group_names_by_indices = questions_by_groups.keys()
-        .enumerate()
-        .fold(BTreeMap::new(), |mut acc, (index, group_name)| {
-            acc.insert(index, group_name.clone());
-            acc
-        });
+/// Returns the same key for the same paths, regardless of their order and how they were written.
+pub fn get_paths_key(paths: &[PathBuf]) -> String {
+    let canonical_paths: BTreeSet<String> = paths.iter()
+        .map(|path| fs::canonicalize(path).unwrap_or(path.clone()).display().to_string())
+        .collect();
+    canonical_paths.into_iter().collect::<Vec<String>>().join(", ")
+}
 
-    (questions_by_groups, group_names_by_indices)
+fn get_group_name_from_file_name(file: &Path) -> String {
+    file.file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .replace(".sll", "")
 }
 
-fn merge_paths_from_matching_groups(mut acc: BTreeMap<String, QuestionGroup>, dir_entry: DirEntry) -> BTreeMap<String, QuestionGroup> {
-    let group_name = dir_entry.file_name()
-        .to_owned()
-        .into_string()
-        .unwrap() // Already filtered
-        .replace(".sll", "");
+fn get_group_name_from_path(input_path: &Path, file: &Path, is_prefixed_with_input_path: bool) -> String {
+    if input_path.is_file() {
+        return get_group_name_from_file_name(file);
+    }
+    let input_path_name = input_path.file_name()
+        .filter(|_| is_prefixed_with_input_path)
+        .map(|name| name.to_string_lossy().to_string());
+    let folder_names = file.strip_prefix(input_path)
+        .unwrap_or(file)
+        .parent()
+        .into_iter()
+        .flat_map(|folder| folder.components())
+        .map(|component| component.as_os_str().to_string_lossy().to_string());
+    input_path_name.into_iter()
+        .chain(folder_names)
+        .chain([get_group_name_from_file_name(file)])
+        .collect::<Vec<String>>()
+        .join(&GROUP_PATH_SEPARATOR.to_string())
+}
+
+fn merge_paths_from_matching_groups(mut acc: BTreeMap<String, QuestionGroup>, (group_name, dir_entry): (String, DirEntry)) -> BTreeMap<String, QuestionGroup> {
     let group_details = acc.entry(group_name.clone())
         .or_default();
     group_details.paths.push(dir_entry.into_path());
     acc
 }
 
-fn get_all_files_under_path(path: PathBuf) -> Vec<DirEntry> {
-    WalkDir::new(path)
+fn get_all_files_under_path(path: PathBuf, diagnostics: &mut Vec<ParseDiagnostic>) -> Vec<DirEntry> {
+    WalkDir::new(&path)
         .into_iter()
-        .filter_map(filter_readable_entries)
+        .filter_map(|result| match result {
+            Ok(dir_entry) => Some(dir_entry),
+            Err(e) => {
+                diagnostics.push(ParseDiagnostic {
+                    file: e.path().unwrap_or(&path).display().to_string(),
+                    line: None,
+                    column: None,
+                    severity: Severity::Warning,
+                    message: format!("Skipping unreadable directory entry: {}", e),
+                });
+                None
+            }
+        })
         .filter(filter_for_files)
         .collect()
 }
 
-fn filter_readable_entries(result: Result<DirEntry, Error>) -> Option<DirEntry> {
-    match result {
-        Ok(res) => Some(res),
-        Err(e) => {
-            print!("Error: Skipping unreadable directory entry with reason: {}", e);
-            None
-        }
-    }
-}
-
 fn filter_for_files(dir_entry: &DirEntry) -> bool {
     !dir_entry.path().is_dir()
 }
 
-pub fn read_all_questions_from_all_paths(paths: &[PathBuf]) -> Vec<Rc<RefCell<Question>>> {
-    paths.iter()
+/// Reads the questions of a group and restores their stats from previous sessions,
+/// saved for the identity of the group or, by earlier versions, for its name.
+/// Unreadable files and malformed lines are skipped and reported in the diagnostics.
+pub fn read_all_questions_from_all_paths(
+    group_name: &str,
+    paths: &[PathBuf],
+    stats_store: &StatsStore,
+    diagnostics: &mut Vec<ParseDiagnostic>,
+) -> Vec<Rc<RefCell<Question>>> {
+    let files: Vec<DirEntry> = paths.iter()
         .cloned()
-        .flat_map(get_all_files_under_path)
-        .filter_map(read_file_to_string_or_skip_on_error)
+        .flat_map(|path| get_all_files_under_path(path, diagnostics))
+        .collect();
+    let lines: Vec<ProcessingStep<String>> = files.into_iter()
+        .filter_map(|file| read_file_to_string(file)
+            .map_err(|diagnostic| diagnostics.push(diagnostic))
+            .ok())
         .flat_map(get_lines_from_string)
-        .filter_map(parse_question_from_line)
+        .collect();
+    let group_id = get_paths_key(paths);
+    lines.into_iter()
+        .filter_map(|line| parse_question_from_line(line)
+            .unwrap_or_else(|diagnostic| {
+                diagnostics.push(diagnostic);
+                None
+            }))
         .fold(BTreeMap::new(), merge_answers_from_matching_questions)
-        .values()
-        .cloned()
+        .into_values()
+        .map(|mut question| {
+            question.group = group_name.to_string();
+            question.group_id = group_id.clone();
+            let stats = stats_store.get(&group_id, &question.question)
+                .or_else(|| stats_store.get(group_name, &question.question));
+            if let Some(stats) = stats {
+                question.load_stats(stats);
+            }
+            question
+        })
         .map(RefCell::new)
         .map(Rc::new)
         .collect()
 }
 
 fn merge_answers_from_matching_questions(mut acc: BTreeMap<String, Question>, new_question: Question) -> BTreeMap<String, Question> {
-    // Merge answers from the same question present in multiple groups.
-    let old_question_op =   acc.get_mut(&new_question.question);
+    // Merge answers from the same question present in multiple lines or files of the group.
+    // The questions are matched like the stats are saved, so e.g. "Dog?" and "dog" share one entry.
+    let key = practice::get_normalized_key(&new_question.question);
+    let old_question_op = acc.get_mut(&key);
     if old_question_op.is_none() {
-        acc.insert(new_question.question.clone(), new_question);
+        acc.insert(key, new_question);
         return acc;
     }
-    let old_question = old_question_op.unwrap();
-    old_question.answers = old_question.answers.union(&new_question.answers).cloned().collect();
-    old_question.locations = old_question.locations.union(&new_question.locations).cloned().collect();
+    old_question_op.unwrap().merge_answers_from(&new_question);
     acc
 }
 
 struct ProcessingStep<T> {
     result: T,
     path: String,
+    /// 1-based line number, `None` before the file is split to lines.
+    line_number: Option<usize>,
 }
 
-fn read_file_to_string_or_skip_on_error(file: DirEntry) -> Option<ProcessingStep<String>> {
+impl<T> ProcessingStep<T> {
+    fn get_diagnostic(&self, column: Option<usize>, message: &str) -> ParseDiagnostic {
+        ParseDiagnostic {
+            file: self.path.clone(),
+            line: self.line_number,
+            column,
+            severity: Severity::Error,
+            message: message.to_string(),
+        }
+    }
+}
+
+fn read_file_to_string(file: DirEntry) -> Result<ProcessingStep<String>, ParseDiagnostic> {
     let path = file.path();
+    let step = ProcessingStep {
+        path: path.display().to_string(),
+        result: String::new(),
+        line_number: None,
+    };
     match fs::read_to_string(path) {
-        Ok(file_str) => {
-            Some(ProcessingStep {
-                path: path.display().to_string(),
-                result: file_str,
-            })
-        }
-        Err(_) => {
-            // Todo display a meaningful error
-            println!("Error: Skipping unreadable file: {}", path.display());
-            None
-        }
+        Ok(file_str) => Ok(ProcessingStep { result: file_str, ..step }),
+        Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+            Err(step.get_diagnostic(None, "Skipping file with invalid content, it must be UTF-8 encoded"))
+        },
+        Err(e) => Err(step.get_diagnostic(None, &format!("Skipping unreadable file: {}", e))),
     }
 }
 
@@ -133,28 +289,66 @@ fn get_lines_from_string(contents: ProcessingStep<String>) -> Vec<ProcessingStep
     file_str
         .lines()
         .map(str::to_owned)
-        .map(|line| ProcessingStep::<String> {
+        .enumerate()
+        .map(|(index, line)| ProcessingStep::<String> {
             result: line,
             path: contents.path.to_owned(),
+            line_number: Some(index + 1),
         })
         .collect()
 }
 
-fn parse_question_from_line(line_contents: ProcessingStep<String>) -> Option<Question> {
-    let line = line_contents.result;
-    let location = line_contents.path;
-    if line.is_empty() || line.starts_with("#") || line.contains(r"^\s+$") {
-        return None; // Skip empty or commented out lines.
+/// Returns the question defined in the line, `None` for empty or commented out lines,
+/// or a diagnostic if the line is malformed.
+fn parse_question_from_line(line_contents: ProcessingStep<String>) -> Result<Option<Question>, ParseDiagnostic> {
+    let line = &line_contents.result;
+    if line.trim().is_empty() || line.trim_start().starts_with("#") {
+        return Ok(None); // Skip empty or commented out lines.
     };
-    let split_q: Vec<&str> = line.split_terminator('|').collect();
-    if split_q.len() != 2 {
-        // TODO: Meaningful error handling
-        print!("Error, skipping malformed question. Location:'{location}' Line: '{line}'");
-        return None;
+    let separator_columns: Vec<usize> = line.chars()
+        .enumerate()
+        .filter(|(_, c)| *c == '|')
+        .map(|(index, _)| index + 1)
+        .collect();
+    if separator_columns.is_empty() {
+        return Err(line_contents.get_diagnostic(None, "Missing '|' separator between the question and the answer"));
+    }
+    if separator_columns.len() > 1 {
+        return Err(line_contents.get_diagnostic(
+            Some(separator_columns[1]),
+            &format!("Unexpected '|' separator, only one is allowed per line (separate multiple answers with '{ANSWER_SEPARATOR}')"),
+        ));
+    }
+    let (question_part, answers_part) = line.split_once('|').unwrap(); // Already checked
+    let question = remove_extra_whitespaces(question_part);
+    let answers: HashSet<String> = split_answers(answers_part).iter()
+        .map(|answer| remove_extra_whitespaces(answer))
+        .filter(|answer| !answer.is_empty())
+        .collect();
+    if question.is_empty() {
+        return Err(line_contents.get_diagnostic(Some(1), "Missing question before the '|' separator"));
+    }
+    if answers.is_empty() {
+        return Err(line_contents.get_diagnostic(Some(separator_columns[0] + 1), "Missing answer after the '|' separator"));
+    }
+    Ok(Some(Question::with_answers(line_contents.path, question, answers)))
+}
+
+/// Splits the answers at the separators, except the escaped ones kept as literal characters.
+fn split_answers(answers_part: &str) -> Vec<String> {
+    let mut answers = vec![String::new()];
+    let mut chars = answers_part.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == ANSWER_SEPARATOR_ESCAPE && chars.peek() == Some(&ANSWER_SEPARATOR) {
+            answers.last_mut().unwrap().push(ANSWER_SEPARATOR);
+            chars.next();
+        } else if c == ANSWER_SEPARATOR {
+            answers.push(String::new());
+        } else {
+            answers.last_mut().unwrap().push(c);
+        }
     }
-    let question = remove_extra_whitespaces(split_q[0]);
-    let answer = remove_extra_whitespaces(split_q[1]);
-    Some(Question::new(location, question, answer))
+    answers
 }
 
 fn remove_extra_whitespaces(text: &str) -> String {
@@ -167,8 +361,8 @@ fn remove_extra_whitespaces(text: &str) -> String {
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashSet;
     use super::*;
+    use crate::practice::Direction;
 
     #[test]
     fn get_paths_from_args_test() {
@@ -184,69 +378,72 @@ mod tests {
     }
 
 
+    fn get_group(paths: &[&str]) -> QuestionGroup {
+        let paths: Vec<PathBuf> = paths.iter().map(PathBuf::from).collect();
+        QuestionGroup {
+            id: get_paths_key(&paths),
+            paths,
+            questions: vec![],
+            is_active: false,
+            diacritics_mode: None,
+            deck: None,
+        }
+    }
+
     #[test]
     fn all_groups_are_collected_from_nested_subdirectories_with_mixed_file_types() {
         // Given
         let paths = vec![PathBuf::from("tests/fixtures/nested_with_mixed_files")];
 
         // When
-        let expected = (
-            BTreeMap::from([
-                ("f0_q1".to_string(), QuestionGroup {
-                    paths: vec![PathBuf::from("tests/fixtures/nested_with_mixed_files/f0_q1.sll")],
-                    questions: vec![],
-                    is_active: false,
-                }),
-                ("f1_q1".to_string(), QuestionGroup {
-                    paths: vec![PathBuf::from("tests/fixtures/nested_with_mixed_files/f1/f1_q1.sll")],
-                    questions: vec![],
-                    is_active: false,
-                }),
-            ]),
-
-            BTreeMap::from([
-                (0, "f0_q1".to_string()),
-                (1, "f1_q1".to_string()),
-            ])
-
-        );
+        let expected = BTreeMap::from([
+            ("f0_q1".to_string(), get_group(&["tests/fixtures/nested_with_mixed_files/f0_q1.sll"])),
+            ("f1/f1_q1".to_string(), get_group(&["tests/fixtures/nested_with_mixed_files/f1/f1_q1.sll"])),
+        ]);
 
         // When
-        let actual = collect_groups_from_multiple_paths(paths);
+        let actual = collect_groups_from_multiple_paths(paths, GroupNaming::ByPath, &mut vec![]);
 
         // Then
         assert_eq!(actual, expected);
     }
 
     #[test]
-    fn all_groups_are_collected_from_multiple_paths() {
+    fn groups_from_multiple_paths_are_named_after_the_input_paths() {
         // Given
         let paths = vec![
             PathBuf::from("tests/fixtures/duplicate_groups/nested_1"),
             PathBuf::from("tests/fixtures/duplicate_groups/nested_2"),
         ];
-        let expected = (
-            BTreeMap::from([
-                ("f0_q1".to_string(), QuestionGroup {
-                    paths: vec![PathBuf::from("tests/fixtures/duplicate_groups/nested_1/f0_q1.sll"), PathBuf::from("tests/fixtures/duplicate_groups/nested_2/f0_q1.sll")],
-                    questions: vec![],
-                    is_active: false,
-                }),
-                ("f1_q1".to_string(), QuestionGroup {
-                    paths: vec![PathBuf::from("tests/fixtures/duplicate_groups/nested_1/f1/f1_q1.sll"), PathBuf::from("tests/fixtures/duplicate_groups/nested_2/f1/f1_q1.sll")],
-                    questions: vec![],
-                    is_active: false,
-                }),
-            ]),
-
-            BTreeMap::from([
-                (0, "f0_q1".to_string()),
-                (1, "f1_q1".to_string()),
-            ])
-        );
+        let expected = BTreeMap::from([
+            ("nested_1/f0_q1".to_string(), get_group(&["tests/fixtures/duplicate_groups/nested_1/f0_q1.sll"])),
+            ("nested_1/f1/f1_q1".to_string(), get_group(&["tests/fixtures/duplicate_groups/nested_1/f1/f1_q1.sll"])),
+            ("nested_2/f0_q1".to_string(), get_group(&["tests/fixtures/duplicate_groups/nested_2/f0_q1.sll"])),
+            ("nested_2/f1/f1_q1".to_string(), get_group(&["tests/fixtures/duplicate_groups/nested_2/f1/f1_q1.sll"])),
+        ]);
+
+        // When
+        let actual = collect_groups_from_multiple_paths(paths, GroupNaming::ByPath, &mut vec![]);
 
-    // When
-        let actual = collect_groups_from_multiple_paths(paths);
+        // Then
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn files_with_the_same_name_are_merged_when_opted_in() {
+        // Given
+        let (group_naming, args) = get_group_naming_from(vec![
+            "tests/fixtures/duplicate_groups/nested_1".to_string(),
+            MERGE_SAME_NAMES_FLAG.to_string(),
+            "tests/fixtures/duplicate_groups/nested_2".to_string(),
+        ]);
+        let expected = BTreeMap::from([
+            ("f0_q1".to_string(), get_group(&["tests/fixtures/duplicate_groups/nested_1/f0_q1.sll", "tests/fixtures/duplicate_groups/nested_2/f0_q1.sll"])),
+            ("f1_q1".to_string(), get_group(&["tests/fixtures/duplicate_groups/nested_1/f1/f1_q1.sll", "tests/fixtures/duplicate_groups/nested_2/f1/f1_q1.sll"])),
+        ]);
+
+        // When
+        let actual = collect_groups_from_multiple_paths(get_paths_from(args), group_naming, &mut vec![]);
 
         // Then
         assert_eq!(actual, expected);
@@ -256,14 +453,16 @@ mod tests {
     fn comments_are_skipped() {
         // Given
         let path = vec![PathBuf::from("tests/fixtures/comment")];
-        let expected = vec![Rc::new(RefCell::new(Question::new(
+        let mut question = Question::new(
             "tests/fixtures/comment".to_string(),
            "question".to_string(),
             "answer".to_string()
-        )))];
+        );
+        question.group_id = get_paths_key(&path);
+        let expected = vec![Rc::new(RefCell::new(question))];
 
         // When
-        let actual = read_all_questions_from_all_paths(&path);
+        let actual = read_all_questions_from_all_paths("comment", &path, &StatsStore::in_memory(), &mut vec![]);
 
         // Then
         assert_eq!(actual, expected);
@@ -276,13 +475,162 @@ mod tests {
         let mut question = Question::new("placeholder".to_string(), "question".to_string(),"placeholder ".to_string());
         question.locations = HashSet::from(["tests/fixtures/collect/f1/f1_q1.sll".to_string(), "tests/fixtures/collect/f0_q2.sll".to_string(), "tests/fixtures/collect/f0_q1.sll".to_string()]);
         question.answers = HashSet::from(["f0_q2 answer".to_string(), "f0_q1 answer".to_string(), "f1_q1 answer".to_string()]);
+        question.group_id = get_paths_key(&paths);
         let expected = vec![Rc::new(RefCell::new(question))];
 
         // When
-        let actual = read_all_questions_from_all_paths(&paths);
+        let actual = read_all_questions_from_all_paths("collect", &paths, &StatsStore::in_memory(), &mut vec![]);
 
         // Then
         assert_eq!(expected, actual);
+        assert_eq!(actual[0].borrow().get_answers_by_locations(), BTreeMap::from([
+            ("tests/fixtures/collect/f0_q1.sll".to_string(), BTreeSet::from(["f0_q1 answer".to_string()])),
+            ("tests/fixtures/collect/f0_q2.sll".to_string(), BTreeSet::from(["f0_q2 answer".to_string()])),
+            ("tests/fixtures/collect/f1/f1_q1.sll".to_string(), BTreeSet::from(["f1_q1 answer".to_string()])),
+        ]));
+    }
+
+    #[test]
+    fn same_question_in_different_groups_is_kept_separate() {
+        // Given
+        let groups = collect_groups_from_multiple_paths(vec![PathBuf::from("tests/fixtures/lint")], GroupNaming::ByPath, &mut vec![]);
+        let read_question = |group_name: &str| read_all_questions_from_all_paths(group_name, &groups[group_name].paths, &StatsStore::in_memory(), &mut vec![])
+            .into_iter()
+            .find(|question| question.borrow().question == "1")
+            .unwrap();
+
+        // When
+        let polish_question = read_question("polish/numbers_pl");
+        let spanish_question = read_question("spanish/numbers_es");
+
+        // Then
+        assert_ne!(polish_question, spanish_question);
+        assert_eq!(polish_question.borrow().answers, HashSet::from(["jeden".to_string()]));
+        assert_eq!(spanish_question.borrow().answers, HashSet::from(["uno".to_string()]));
+    }
+
+    #[test]
+    fn malformed_lines_are_skipped_and_reported() {
+        // Given
+        let paths = vec![PathBuf::from("tests/fixtures/malformed")];
+        let file = "tests/fixtures/malformed/malformed.sll".to_string();
+        let get_expected_diagnostic = |line: usize, column: Option<usize>, message: &str| ParseDiagnostic {
+            file: file.clone(),
+            line: Some(line),
+            column,
+            severity: Severity::Error,
+            message: message.to_string(),
+        };
+        let expected_diagnostics = vec![
+            get_expected_diagnostic(3, None, "Missing '|' separator between the question and the answer"),
+            get_expected_diagnostic(4, Some(11), "Unexpected '|' separator, only one is allowed per line (separate multiple answers with ';')"),
+            get_expected_diagnostic(5, Some(1), "Missing question before the '|' separator"),
+            get_expected_diagnostic(6, Some(17), "Missing answer after the '|' separator"),
+        ];
+        let mut diagnostics = vec![];
+
+        // When
+        let actual = read_all_questions_from_all_paths("malformed", &paths, &StatsStore::in_memory(), &mut diagnostics);
+
+        // Then
+        assert_eq!(actual.len(), 1);
+        assert_eq!(actual[0].borrow().question, "question");
+        assert_eq!(diagnostics, expected_diagnostics);
+    }
+
+    #[test]
+    fn multiple_answers_in_a_single_line_are_collected() {
+        // Given
+        let paths = vec![PathBuf::from("tests/fixtures/multiple_answers")];
+        let expected_answers = HashSet::from(["pies".to_string(), "psa".to_string(), "psem".to_string()]);
+
+        // When
+        let actual = read_all_questions_from_all_paths("multiple_answers", &paths, &StatsStore::in_memory(), &mut vec![]);
+
+        // Then
+        assert_eq!(actual.len(), 1);
+        assert_eq!(actual[0].borrow().answers, expected_answers);
+    }
+
+    #[test]
+    fn questions_differing_in_case_and_punctuation_are_merged() {
+        // Given
+        let questions = [("Dog?", "pies"), ("dog", "psa")]
+            .map(|(question, answer)| Question::new(String::new(), question.to_string(), answer.to_string()));
+
+        // When
+        let actual = questions.into_iter().fold(BTreeMap::new(), merge_answers_from_matching_questions);
+
+        // Then
+        assert_eq!(actual.len(), 1);
+        let question = actual.values().next().unwrap();
+        assert_eq!(question.question, "Dog?");
+        assert_eq!(question.answers, HashSet::from(["pies".to_string(), "psa".to_string()]));
+    }
+
+    #[test]
+    fn escaped_answer_separators_are_kept_in_the_answer() {
+        // Given
+        let answers_part = r" \;) ; :-) ; a\b ";
+
+        // When
+        let actual = split_answers(answers_part);
+
+        // Then
+        assert_eq!(actual, vec![" ;) ", " :-) ", r" a\b "]);
+    }
+
+    #[test]
+    fn stats_from_previous_sessions_are_restored() {
+        // Given
+        let path = vec![PathBuf::from("tests/fixtures/comment")];
+        let mut practiced_question = Question::new(String::new(), "question".to_string(), "answer".to_string());
+        practiced_question.group_id = get_paths_key(&path);
+        practiced_question.increment_error_count(Direction::Forward, 4);
+        let mut stats_store = StatsStore::in_memory();
+        stats_store.update(&practiced_question);
+
+        // When
+        let actual = read_all_questions_from_all_paths("comment", &path, &stats_store, &mut vec![]);
+
+        // Then
+        assert_eq!(actual[0].borrow().group, "comment");
+        assert_eq!(actual[0].borrow().get_error_count_sum(Direction::Forward), 4);
+        assert_eq!(actual[0].borrow().get_error_count_for_round(Direction::Forward), 0);
+    }
+
+    #[test]
+    fn stats_saved_for_the_group_name_are_restored() {
+        // Given
+        let path = vec![PathBuf::from("tests/fixtures/comment")];
+        let mut practiced_question = Question::new(String::new(), "question".to_string(), "answer".to_string());
+        practiced_question.group_id = "comment".to_string();
+        practiced_question.increment_error_count(Direction::Forward, 2);
+        let mut stats_store = StatsStore::in_memory();
+        stats_store.update(&practiced_question);
+
+        // When
+        let actual = read_all_questions_from_all_paths("comment", &path, &stats_store, &mut vec![]);
+
+        // Then
+        assert_eq!(actual[0].borrow().group_id, get_paths_key(&path));
+        assert_eq!(actual[0].borrow().get_error_count_sum(Direction::Forward), 2);
+    }
+
+    #[test]
+    fn group_id_does_not_depend_on_the_input_path() {
+        // Given
+        let parent_path = vec![PathBuf::from("tests/fixtures/duplicate_groups")];
+        let nested_path = vec![PathBuf::from("./tests/fixtures/duplicate_groups/nested_1/../nested_1")];
+
+        // When
+        let from_parent = collect_groups_from_multiple_paths(parent_path, GroupNaming::ByPath, &mut vec![]);
+        let from_nested = collect_groups_from_multiple_paths(nested_path, GroupNaming::ByPath, &mut vec![]);
+
+        // Then
+        let parent_group = &from_parent["nested_1/f0_q1"];
+        let nested_group = &from_nested["f0_q1"];
+        assert_eq!(parent_group.id, nested_group.id);
     }
 
     #[test]
@@ -291,7 +639,7 @@ mod tests {
         let input = "     my       question ";
         let expected = "my question".to_owned();
         // When
-        let result = remove_extra_whitespaces(&input);
+        let result = remove_extra_whitespaces(input);
         // Then
         assert_eq!(expected, result);
     }
@@ -0,0 +1,99 @@
+use std::ops::Range;
+use regex::{Regex, RegexBuilder};
+use strum::EnumMessage;
+use crate::question::Question;
+
+/// Defines how the search text is matched.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, EnumMessage)]
+pub enum SearchMode {
+    /// Case-insensitive substring search.
+    #[default]
+    #[strum(message="Text")]
+    Text,
+    /// Case-insensitive regular expression.
+    #[strum(message="Regex")]
+    Regex,
+}
+
+impl SearchMode {
+    pub fn next(&self) -> SearchMode {
+        match self {
+            SearchMode::Text => SearchMode::Regex,
+            SearchMode::Regex => SearchMode::Text,
+        }
+    }
+}
+
+/// A compiled search over group names, questions and answers.
+#[derive(Debug, Clone)]
+pub struct SearchQuery {
+    regex: Regex,
+}
+
+impl SearchQuery {
+    pub fn new(text: &str, mode: SearchMode) -> Result<Self, regex::Error> {
+        let pattern = match mode {
+            SearchMode::Text => regex::escape(text),
+            SearchMode::Regex => text.to_string(),
+        };
+        let regex = RegexBuilder::new(&pattern)
+            .case_insensitive(true)
+            .build()?;
+        Ok(Self { regex })
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        self.regex.is_match(text)
+    }
+
+    /// Returns the byte ranges of the non-empty matches in the text, for highlighting.
+    pub fn find_matches(&self, text: &str) -> Vec<Range<usize>> {
+        self.regex.find_iter(text)
+            .filter(|found| !found.is_empty())
+            .map(|found| found.range())
+            .collect()
+    }
+
+    pub fn matches_question(&self, question: &Question) -> bool {
+        self.is_match(&question.question) || question.answers.iter().any(|answer| self.is_match(answer))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_search_is_case_insensitive_and_escapes_special_characters() {
+        // Given
+        let query = SearchQuery::new("Dog?", SearchMode::Text).unwrap();
+
+        // When
+        let matches = query.find_matches("a dog? a DOG? dogs");
+
+        // Then
+        assert_eq!(matches, vec![2..6, 9..13]);
+        assert!(!query.is_match("dogs"));
+    }
+
+    #[test]
+    fn regex_search_matches_answers_too() {
+        // Given
+        let query = SearchQuery::new("^p(ie|s)", SearchMode::Regex).unwrap();
+        let mut question = Question::new(String::new(), String::from("dog"), String::from("pies"));
+        question.answers.insert(String::from("psa"));
+        let other_question = Question::new(String::new(), String::from("cat"), String::from("kot"));
+
+        // When
+        let actual = (query.matches_question(&question), query.matches_question(&other_question));
+
+        // Then
+        assert_eq!(actual, (true, false));
+    }
+
+    #[test]
+    fn invalid_regex_is_rejected() {
+        assert!(SearchQuery::new("(pies", SearchMode::Regex).is_err());
+        assert!(SearchQuery::new("(pies", SearchMode::Text).is_ok());
+    }
+}
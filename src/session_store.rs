@@ -0,0 +1,65 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use crate::practice::{AnswerMode, DiacriticsMode, DirectionSetting};
+use crate::question_parser::get_paths_key;
+use crate::json_store::JsonStore;
+
+pub const SESSIONS_FILE_NAME: &str = "sessions.json";
+
+/// The selections and options restored on the next start with the same input paths.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionState {
+    /// The identities of the active groups.
+    pub active_groups: BTreeSet<String>,
+    pub setup_body_left_size: Option<u16>,
+    pub practice_body_left_size: Option<u16>,
+    pub direction_setting: DirectionSetting,
+    pub answer_mode: AnswerMode,
+    pub diacritics_mode: DiacriticsMode,
+    pub mastery_streak: Option<u16>,
+}
+
+/// The last session state for every set of input paths the app was started with.
+pub type SessionStore = JsonStore<BTreeMap<String, SessionState>>;
+
+impl SessionStore {
+    pub fn get(&self, input_paths: &[PathBuf]) -> Option<&SessionState> {
+        self.get_data().get(&get_paths_key(input_paths))
+    }
+
+    pub fn update(&mut self, input_paths: &[PathBuf], state: SessionState) {
+        self.get_data_mut().insert(get_paths_key(input_paths), state);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json_store::get_temp_store_path;
+
+    #[test]
+    fn session_is_restored_for_the_same_input_paths() {
+        // Given
+        let path = get_temp_store_path("session_is_restored_for_the_same_input_paths", SESSIONS_FILE_NAME);
+        let mut store = SessionStore::load_from(path.clone()).unwrap();
+        let state = SessionState {
+            active_groups: BTreeSet::from(["f0_q1".to_string()]),
+            setup_body_left_size: Some(50),
+            answer_mode: AnswerMode::MultipleChoice,
+            mastery_streak: Some(4),
+            ..SessionState::default()
+        };
+
+        // When
+        store.update(&[PathBuf::from("tests/fixtures/collect"), PathBuf::from("tests/fixtures/comment")], state.clone());
+        store.save().unwrap();
+        let restored = SessionStore::load_from(path).unwrap();
+
+        // Then
+        assert_eq!(restored.get(&[PathBuf::from("tests/fixtures/comment"), PathBuf::from("./tests/fixtures/collect")]), Some(&state));
+        assert_eq!(restored.get(&[PathBuf::from("tests/fixtures/collect")]), None);
+    }
+}
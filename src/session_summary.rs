@@ -0,0 +1,307 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashSet};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+use crate::practice;
+use crate::practice::Direction;
+use crate::question::Question;
+use crate::json_store::APP_DIR_NAME;
+
+const SUMMARY_DIR_NAME: &str = "summaries";
+const HARDEST_QUESTION_LIMIT: usize = 5;
+
+/// Counters of a practice session, from starting the practice until ending it.
+#[derive(Debug)]
+pub struct SessionStats {
+    started_at: Instant,
+    clues_revealed: u32,
+    answers_revealed: u32,
+    attempts_by_groups: BTreeMap<String, GroupAttempts>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+struct GroupAttempts {
+    correct_count: u32,
+    incorrect_count: u32,
+    questions: HashSet<String>,
+}
+
+impl Default for SessionStats {
+    fn default() -> Self {
+        Self {
+            started_at: Instant::now(),
+            clues_revealed: 0,
+            answers_revealed: 0,
+            attempts_by_groups: BTreeMap::new(),
+        }
+    }
+}
+
+impl SessionStats {
+    pub fn record_attempt(&mut self, question: &Question, is_accepted: bool) {
+        let group_attempts = self.attempts_by_groups.entry(question.group.clone()).or_default();
+        if is_accepted {
+            group_attempts.correct_count += 1;
+        } else {
+            group_attempts.incorrect_count += 1;
+        }
+        group_attempts.questions.insert(question.question.clone());
+    }
+
+    pub fn record_clue_reveal(&mut self) {
+        self.clues_revealed += 1;
+    }
+
+    pub fn record_answer_reveal(&mut self) {
+        self.answers_revealed += 1;
+    }
+}
+
+/// Attempts of a single group in a session.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupSummary {
+    pub group: String,
+    pub question_count: usize,
+    pub attempt_count: u32,
+    pub correct_count: u32,
+}
+
+/// The end-of-session report.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionSummary {
+    /// Shown above the figures, e.g. when the session ended by completing a round.
+    pub headline: Option<String>,
+    pub time_spent: Duration,
+    pub clues_revealed: u32,
+    pub answers_revealed: u32,
+    /// The mean time from displaying a question to an attempt, `None` without attempts.
+    pub average_response_time: Option<Duration>,
+    /// Questions with errors in the round and their error counts, the hardest first.
+    pub hardest_questions: Vec<(String, u16)>,
+    pub groups: Vec<GroupSummary>,
+}
+
+impl SessionSummary {
+    /// Creates the report from the session counters and the round stats of the practiced questions.
+    pub fn new(stats: &SessionStats, questions: &[Rc<RefCell<Question>>], directions: &[Direction]) -> Self {
+        let groups = stats.attempts_by_groups.iter()
+            .map(|(group, attempts)| GroupSummary {
+                group: group.clone(),
+                question_count: attempts.questions.len(),
+                attempt_count: attempts.correct_count + attempts.incorrect_count,
+                correct_count: attempts.correct_count,
+            })
+            .collect();
+        let response_times: Vec<Duration> = questions.iter()
+            .flat_map(|question| directions.iter()
+                .flat_map(|direction| question.borrow().get_response_times_for_round(*direction).to_vec())
+                .collect::<Vec<Duration>>())
+            .collect();
+        let average_response_time = practice::get_mean_duration(&response_times);
+        let hardest_questions = practice::get_hardest_questions_in_round(questions, HARDEST_QUESTION_LIMIT, directions).iter()
+            .map(|question| {
+                let question = question.borrow();
+                let round_errors = directions.iter()
+                    .map(|direction| question.get_error_count_for_round(*direction))
+                    .fold(0u16, |sum, errors| sum.saturating_add(errors));
+                (question.question.clone(), round_errors)
+            })
+            .filter(|(_, round_errors)| *round_errors > 0)
+            .collect();
+        Self {
+            headline: None,
+            time_spent: stats.started_at.elapsed(),
+            clues_revealed: stats.clues_revealed,
+            answers_revealed: stats.answers_revealed,
+            average_response_time,
+            hardest_questions,
+            groups,
+        }
+    }
+
+    pub fn get_question_count(&self) -> usize {
+        self.groups.iter().map(|group| group.question_count).sum()
+    }
+
+    pub fn get_attempt_count(&self) -> u32 {
+        self.groups.iter().map(|group| group.attempt_count).sum()
+    }
+
+    pub fn get_correct_count(&self) -> u32 {
+        self.groups.iter().map(|group| group.correct_count).sum()
+    }
+
+    /// Returns the report as lines of text, shared by the summary screen and the export.
+    pub fn get_report_lines(&self) -> Vec<String> {
+        let mut lines: Vec<String> = self.headline.iter()
+            .flat_map(|headline| [headline.clone(), String::new()])
+            .collect();
+        lines.extend([
+            format!("Time spent: {}", format_duration(self.time_spent)),
+            format!("Questions attempted: {}", self.get_question_count()),
+            format!(
+                "Accuracy: {} ({} of {} attempts correct)",
+                format_accuracy(self.get_correct_count(), self.get_attempt_count()),
+                self.get_correct_count(),
+                self.get_attempt_count()
+            ),
+            format!(
+                "Average response time: {}",
+                self.average_response_time
+                    .map(|time| format!("{:.1}s", time.as_secs_f32()))
+                    .unwrap_or("-".to_string())
+            ),
+            format!("Clues revealed: {}", self.clues_revealed),
+            format!("Answers revealed: {}", self.answers_revealed),
+            String::new(),
+            "Hardest items:".to_string(),
+        ]);
+        if self.hardest_questions.is_empty() {
+            lines.push("  None, well done!".to_string());
+        }
+        lines.extend(self.hardest_questions.iter()
+            .map(|(question, errors)| format!("  {question} (errors: {errors})")));
+        lines.push(String::new());
+        lines.push("Groups:".to_string());
+        lines.extend(self.groups.iter()
+            .map(|group| format!(
+                "  {}: questions: {}, attempts: {}, accuracy: {}",
+                group.group,
+                group.question_count,
+                group.attempt_count,
+                format_accuracy(group.correct_count, group.attempt_count)
+            )));
+        lines
+    }
+
+    /// Writes the report to a new text file in the given directory and returns its path.
+    pub fn export_to(&self, dir: &Path, timestamp: u64) -> Result<PathBuf, Box<dyn Error>> {
+        fs::create_dir_all(dir)?;
+        let path = dir.join(format!("session_{timestamp}.txt"));
+        fs::write(&path, self.get_report_lines().join("\n") + "\n")?;
+        Ok(path)
+    }
+
+    /// Returns the directory of the exported summaries under the user's data directory.
+    pub fn get_default_export_dir() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join(APP_DIR_NAME).join(SUMMARY_DIR_NAME))
+    }
+}
+
+fn format_accuracy(correct_count: u32, attempt_count: u32) -> String {
+    if attempt_count == 0 {
+        return "-".to_string();
+    }
+    format!("{}%", correct_count * 100 / attempt_count)
+}
+
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    match seconds {
+        0..60 => format!("{seconds}s"),
+        60..3600 => format!("{}m {}s", seconds / 60, seconds % 60),
+        _ => format!("{}h {}m", seconds / 3600, seconds % 3600 / 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_question(group: &str, question: &str) -> Rc<RefCell<Question>> {
+        let mut question = Question::new(String::new(), question.to_string(), "answer".to_string());
+        question.group = group.to_string();
+        Rc::new(RefCell::new(question))
+    }
+
+    #[test]
+    fn attempts_are_summarized_per_group() {
+        // Given
+        let mut stats = SessionStats::default();
+        let question_1 = get_question("group_1", "question 1");
+        let question_2 = get_question("group_1", "question 2");
+        let question_3 = get_question("group_2", "question 3");
+
+        // When
+        stats.record_attempt(&question_1.borrow(), false);
+        stats.record_attempt(&question_1.borrow(), true);
+        stats.record_attempt(&question_2.borrow(), true);
+        stats.record_attempt(&question_3.borrow(), false);
+        stats.record_clue_reveal();
+        let summary = SessionSummary::new(&stats, &[], &[Direction::Forward]);
+
+        // Then
+        assert_eq!(summary.get_question_count(), 3);
+        assert_eq!(summary.get_attempt_count(), 4);
+        assert_eq!(summary.get_correct_count(), 2);
+        assert_eq!(summary.clues_revealed, 1);
+        assert_eq!(summary.groups, vec![
+            GroupSummary { group: "group_1".to_string(), question_count: 2, attempt_count: 3, correct_count: 2 },
+            GroupSummary { group: "group_2".to_string(), question_count: 1, attempt_count: 1, correct_count: 0 },
+        ]);
+    }
+
+    #[test]
+    fn hardest_questions_without_errors_in_the_round_are_left_out() {
+        // Given
+        let stats = SessionStats::default();
+        let missed = get_question("group", "missed");
+        missed.borrow_mut().increment_error_count(Direction::Forward, 2);
+        missed.borrow_mut().increment_error_count(Direction::Reverse, 1);
+        let answered = get_question("group", "answered");
+
+        // When
+        let summary = SessionSummary::new(&stats, &[missed, answered], &[Direction::Forward, Direction::Reverse]);
+
+        // Then
+        assert_eq!(summary.hardest_questions, vec![("missed".to_string(), 3)]);
+    }
+
+    #[test]
+    fn average_response_time_is_calculated_from_every_attempt() {
+        // Given
+        let question_1 = get_question("group", "question 1");
+        question_1.borrow_mut().record_response_time(Direction::Forward, Duration::from_secs(1));
+        question_1.borrow_mut().record_response_time(Direction::Forward, Duration::from_secs(2));
+        let question_2 = get_question("group", "question 2");
+        question_2.borrow_mut().record_response_time(Direction::Forward, Duration::from_secs(6));
+        question_2.borrow_mut().record_response_time(Direction::Reverse, Duration::from_secs(60));
+
+        // When
+        let summary = SessionSummary::new(&SessionStats::default(), &[question_1, question_2], &[Direction::Forward]);
+
+        // Then
+        assert_eq!(summary.average_response_time, Some(Duration::from_secs(3)));
+    }
+
+    #[test]
+    fn report_contains_accuracy_and_time_spent() {
+        // Given
+        let mut summary = SessionSummary::new(&SessionStats::default(), &[], &[Direction::Forward]);
+        summary.time_spent = Duration::from_secs(125);
+        summary.groups = vec![GroupSummary { group: "group".to_string(), question_count: 2, attempt_count: 3, correct_count: 2 }];
+
+        // When
+        let lines = summary.get_report_lines();
+
+        // Then
+        assert!(lines.contains(&"Time spent: 2m 5s".to_string()));
+        assert!(lines.contains(&"Accuracy: 66% (2 of 3 attempts correct)".to_string()));
+        assert!(lines.contains(&"  group: questions: 2, attempts: 3, accuracy: 66%".to_string()));
+    }
+
+    #[test]
+    fn accuracy_is_not_shown_without_attempts() {
+        // Given
+        let summary = SessionSummary::new(&SessionStats::default(), &[], &[Direction::Forward]);
+
+        // When
+        let lines = summary.get_report_lines();
+
+        // Then
+        assert!(lines.contains(&"Accuracy: - (0 of 0 attempts correct)".to_string()));
+    }
+}
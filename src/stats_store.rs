@@ -0,0 +1,87 @@
+use std::collections::BTreeMap;
+use crate::json_store::JsonStore;
+use crate::practice;
+use crate::question::{Question, QuestionStats};
+
+pub const STATS_FILE_NAME: &str = "stats.json";
+
+/// Practice stats of every question seen so far, keyed by group identity and the normalized question text.
+pub type StatsStore = JsonStore<BTreeMap<String, BTreeMap<String, QuestionStats>>>;
+
+impl StatsStore {
+    pub fn get(&self, group: &str, question: &str) -> Option<&QuestionStats> {
+        self.get_data().get(group)?
+            .get(&get_question_key(question))
+    }
+
+    pub fn update(&mut self, question: &Question) {
+        self.get_data_mut().entry(question.group_id.clone())
+            .or_default()
+            .insert(get_question_key(&question.question), question.get_stats());
+    }
+}
+
+fn get_question_key(question: &str) -> String {
+    practice::get_normalized_key(question)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use crate::json_store::get_temp_store_path;
+    use crate::practice::Direction;
+
+    #[test]
+    fn stats_are_restored_after_saving() {
+        // Given
+        let path = get_temp_store_path("stats_are_restored_after_saving", STATS_FILE_NAME);
+        let mut question = Question::new(String::new(), "The Question?".to_string(), "answer".to_string());
+        question.group_id = "group".to_string();
+        question.increment_correct_count(Direction::Forward, 3);
+        question.increment_error_count(Direction::Reverse, 2);
+
+        // When
+        let mut store = StatsStore::load_from(path.clone()).unwrap();
+        store.update(&question);
+        store.save().unwrap();
+        let actual = StatsStore::load_from(path).unwrap();
+
+        // Then
+        question.reset_round_stats(); // Round stats are not saved
+        assert_eq!(actual.get("group", "the question"), Some(&question.get_stats()));
+    }
+
+    #[test]
+    fn stats_saved_without_directions_are_loaded_as_forward_stats() {
+        // Given
+        let path = get_temp_store_path("stats_saved_without_directions_are_loaded_as_forward_stats", STATS_FILE_NAME);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, r#"{"group": {"question": {"correct_count_sum": 1, "error_count_sum": 2}}}"#).unwrap();
+        let mut question = Question::new(String::new(), "question".to_string(), "answer".to_string());
+
+        // When
+        let store = StatsStore::load_from(path).unwrap();
+        question.load_stats(store.get("group", "question").unwrap());
+
+        // Then
+        assert_eq!(question.get_error_count_sum(Direction::Forward), 2);
+        assert_eq!(question.get_error_count_sum(Direction::Reverse), 0);
+    }
+
+    #[test]
+    fn stats_are_kept_separate_per_group() {
+        // Given
+        let mut store = StatsStore::in_memory();
+        let mut question = Question::new(String::new(), "question".to_string(), "answer".to_string());
+        question.group_id = "group_1".to_string();
+        question.increment_error_count(Direction::Forward, 1);
+
+        // When
+        store.update(&question);
+
+        // Then
+        assert!(store.get("group_1", "question").is_some());
+        assert!(store.get("group_2", "question").is_none());
+    }
+}
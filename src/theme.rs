@@ -0,0 +1,185 @@
+use ratatui::style::{Color, Modifier, Style};
+use serde::{Deserialize, Serialize};
+use crate::practice::AttemptResult;
+
+/// The environment variable disabling colors in terminal apps, see https://no-color.org
+const NO_COLOR_VARIABLE: &str = "NO_COLOR";
+
+/// The named palettes to pick from with the `appearance.theme` setting or the `--theme` flag.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThemeName {
+    /// Colors for terminals with a dark background.
+    #[default]
+    Default,
+    /// Darker colors for terminals with a light background.
+    LightTerminal,
+    /// Bright colors and bold text, without dimmed text.
+    HighContrast,
+    /// Text styles only, without colors. Used when `NO_COLOR` is set and no theme is configured.
+    Monochrome,
+}
+
+/// Returns the configured theme, or the monochrome one if colors are disabled with the `NO_COLOR` variable.
+pub fn get_theme_name(configured: Option<ThemeName>, no_color: Option<&str>) -> ThemeName {
+    match (configured, no_color) {
+        (Some(name), _) => name,
+        (None, Some(value)) if !value.is_empty() => ThemeName::Monochrome,
+        (None, _) => ThemeName::Default,
+    }
+}
+
+pub fn get_theme_name_from_env(configured: Option<ThemeName>) -> ThemeName {
+    get_theme_name(configured, std::env::var(NO_COLOR_VARIABLE).ok().as_deref())
+}
+
+/// The styles of the UI, named after what they mean rather than how they look.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    /// The contents of the focused pane.
+    pub focused: Style,
+    /// The contents of the other panes.
+    pub unfocused: Style,
+    pub border: Style,
+    /// The help lines and hints.
+    pub hint: Style,
+    /// The headers of tables and reports.
+    pub header: Style,
+    /// The selected row of a list or table.
+    pub highlight: Style,
+    pub search_match: Style,
+    /// The selected groups, marked questions and the bars of the box chart.
+    pub active: Style,
+    /// The values written on the bars of the box chart.
+    pub active_value: Style,
+    /// Correct answers and missing characters of the wrong ones.
+    pub correct: Style,
+    /// Answers accepted with a few typos, and wrong characters.
+    pub almost: Style,
+    /// Wrong answers, extra characters and running out of time.
+    pub wrong: Style,
+    /// The exit and notification popups.
+    pub popup: Style,
+    pub warning: Style,
+    pub error: Style,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::new(ThemeName::Default)
+    }
+}
+
+impl Theme {
+    pub fn new(name: ThemeName) -> Self {
+        match name {
+            ThemeName::Default => Theme {
+                focused: Style::new(),
+                unfocused: Style::new().dim(),
+                border: Style::new().dim(),
+                hint: Style::new().dim(),
+                header: Style::new().bold(),
+                highlight: Style::new().fg(Color::Black).bg(Color::White),
+                search_match: Style::new().fg(Color::Black).bg(Color::Yellow),
+                active: Style::new().fg(Color::Green),
+                active_value: Style::new().fg(Color::Black).bg(Color::Green),
+                correct: Style::new().fg(Color::Green),
+                almost: Style::new().fg(Color::Yellow),
+                wrong: Style::new().fg(Color::Red),
+                popup: Style::new().fg(Color::Red),
+                warning: Style::new().fg(Color::Yellow),
+                error: Style::new().fg(Color::Red),
+            },
+            ThemeName::LightTerminal => Theme {
+                focused: Style::new(),
+                unfocused: Style::new().fg(Color::DarkGray),
+                border: Style::new().fg(Color::DarkGray),
+                hint: Style::new().fg(Color::DarkGray),
+                header: Style::new().bold(),
+                highlight: Style::new().fg(Color::White).bg(Color::Blue),
+                search_match: Style::new().fg(Color::Black).bg(Color::LightYellow),
+                active: Style::new().fg(Color::Blue),
+                active_value: Style::new().fg(Color::White).bg(Color::Blue),
+                correct: Style::new().fg(Color::Green),
+                almost: Style::new().fg(Color::Magenta),
+                wrong: Style::new().fg(Color::Red),
+                popup: Style::new().fg(Color::Red),
+                warning: Style::new().fg(Color::Magenta),
+                error: Style::new().fg(Color::Red),
+            },
+            ThemeName::HighContrast => Theme {
+                focused: Style::new().fg(Color::White),
+                unfocused: Style::new().fg(Color::Gray),
+                border: Style::new().fg(Color::White),
+                hint: Style::new().fg(Color::White),
+                header: Style::new().fg(Color::White).bold().underlined(),
+                highlight: Style::new().fg(Color::Black).bg(Color::LightYellow).bold(),
+                search_match: Style::new().fg(Color::Black).bg(Color::LightCyan).bold(),
+                active: Style::new().fg(Color::LightGreen).bold(),
+                active_value: Style::new().fg(Color::Black).bg(Color::LightGreen).bold(),
+                correct: Style::new().fg(Color::LightGreen).bold(),
+                almost: Style::new().fg(Color::LightYellow).bold(),
+                wrong: Style::new().fg(Color::LightRed).bold(),
+                popup: Style::new().fg(Color::LightYellow).bold(),
+                warning: Style::new().fg(Color::LightYellow).bold(),
+                error: Style::new().fg(Color::LightRed).bold(),
+            },
+            ThemeName::Monochrome => Theme {
+                focused: Style::new(),
+                unfocused: Style::new().dim(),
+                border: Style::new(),
+                hint: Style::new().dim(),
+                header: Style::new().bold(),
+                highlight: Style::new().add_modifier(Modifier::REVERSED),
+                search_match: Style::new().bold().underlined(),
+                active: Style::new().bold(),
+                active_value: Style::new().add_modifier(Modifier::REVERSED),
+                correct: Style::new().bold(),
+                almost: Style::new().italic(),
+                wrong: Style::new().bold().italic(),
+                popup: Style::new().bold(),
+                warning: Style::new().italic(),
+                error: Style::new().bold(),
+            },
+        }
+    }
+
+    /// Returns the style of the feedback on the previous attempt.
+    pub fn get_feedback_style(&self, result: Option<&AttemptResult>) -> Style {
+        match result {
+            None => Style::new(),
+            Some(AttemptResult::Correct | AttemptResult::DiacriticsMismatch { .. }) => self.correct,
+            Some(AttemptResult::AlmostCorrect { .. }) => self.almost,
+            Some(AttemptResult::Incorrect | AttemptResult::TimedOut { .. }) => self.wrong,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn configured_theme_wins_over_no_color() {
+        assert_eq!(get_theme_name(None, None), ThemeName::Default);
+        assert_eq!(get_theme_name(None, Some("")), ThemeName::Default);
+        assert_eq!(get_theme_name(None, Some("1")), ThemeName::Monochrome);
+        assert_eq!(get_theme_name(Some(ThemeName::HighContrast), Some("1")), ThemeName::HighContrast);
+    }
+
+    #[test]
+    fn monochrome_theme_has_no_colors() {
+        // Given
+        let theme = Theme::new(ThemeName::Monochrome);
+
+        // When
+        let styles = [
+            theme.focused, theme.unfocused, theme.border, theme.hint, theme.header, theme.highlight, theme.search_match,
+            theme.active, theme.active_value, theme.correct, theme.almost, theme.wrong, theme.popup, theme.warning, theme.error,
+        ];
+
+        // Then
+        assert!(styles.iter().all(|style| style.fg.is_none() && style.bg.is_none()));
+        assert_ne!(theme.get_feedback_style(Some(&AttemptResult::Correct)), theme.get_feedback_style(Some(&AttemptResult::Incorrect)));
+    }
+}
@@ -1,16 +1,22 @@
 use crate::app::{AppPhase, AppState, PopupDetails, UiComponent};
-use crate::{ ui_setup_phase, ui_practice_phase};
-use ratatui::prelude::Color;
+use crate::{help, ui_setup_phase, ui_practice_phase};
 use ratatui::style::Style;
 use ratatui::{
     layout::{Constraint, Layout},
     widgets::{Block, Padding},
     Frame,
 };
-use ratatui::layout::{Alignment, Flex, Rect};
+use ratatui::text::Line;
+use ratatui::layout::{Alignment, Flex, Margin, Rect};
 use ratatui_widgets::borders::BorderType;
 use ratatui_widgets::clear::Clear;
-use ratatui_widgets::paragraph::Paragraph;
+use ratatui_widgets::list::{List, ListItem};
+use ratatui_widgets::paragraph::{Paragraph, Wrap};
+use ratatui_widgets::scrollbar::ScrollbarState;
+use crate::keymap::Action;
+use crate::practice::LEITNER_BOX_COUNT;
+use crate::question_parser::Severity;
+use crate::ui_shared;
 
 pub fn draw_ui(frame: &mut Frame, app: &mut AppState) {
     let app_phase = app.get_app_phase_for_active_component();
@@ -26,32 +32,41 @@ pub fn draw_ui(frame: &mut Frame, app: &mut AppState) {
     let title_block = Block::bordered()
         .title("[ Shellingo ]")
         .border_type(BorderType::Plain)
-        .border_style(Style::new().dim())
+        .border_style(app.theme.border)
         .padding(Padding::horizontal(1));
 
     // Body layout
     let [body_left_area, body_right_area]  = Layout::horizontal(
         match app_phase {
             AppPhase::Setup => [
-                Constraint::Length(*app.setup_body_left_size.get_or_insert(45)),
+                Constraint::Length(*app.setup_body_left_size.get_or_insert(app.config.layout.setup_left_pane_width)),
                 Constraint::Fill(1)
             ],
             AppPhase::Practice => [
-                Constraint::Length(*app.practice_body_left_size.get_or_insert(22)),
+                Constraint::Length(*app.practice_body_left_size.get_or_insert(app.config.layout.practice_left_pane_width)),
                 Constraint::Fill(1)
             ],
         }
     ).areas(body_area);
 
+    app.mouse_areas.body_left = body_left_area;
+    app.mouse_areas.body_right = body_right_area;
+
     // Render contents
     match app_phase {
         AppPhase::Setup => {
-            ui_setup_phase::render_title_with_help_text(frame, title_block, help_area);
+            ui_setup_phase::render_title_with_help_text(app, frame, title_block, help_area);
             ui_setup_phase::render_group_list_with_scrollbar(app, frame, body_left_area);
-            ui_setup_phase::render_question_table_with_scrollbar(app, frame, body_right_area);
+            let [question_table_area, leitner_box_chart_area] = Layout::vertical([
+                Constraint::Fill(1),
+                Constraint::Length(LEITNER_BOX_COUNT as u16 + 2),
+            ]).areas(body_right_area);
+            app.mouse_areas.question_table = question_table_area;
+            ui_setup_phase::render_question_table_with_scrollbar(app, frame, question_table_area);
+            ui_setup_phase::render_leitner_box_chart(app, frame, leitner_box_chart_area);
         },
         AppPhase::Practice => {
-            ui_practice_phase::render_title_with_help_text(frame, title_block, help_area);
+            ui_practice_phase::render_title_with_help_text(app, frame, title_block, help_area);
             ui_practice_phase::render_practice_controls(app, frame, body_left_area);
             ui_practice_phase::render_practice_main(app, frame, body_right_area);
         }
@@ -61,20 +76,166 @@ pub fn draw_ui(frame: &mut Frame, app: &mut AppState) {
     if [UiComponent::ExitPopup, UiComponent::NotificationPopup].contains(&app.get_active_component()) {
         let popup_details = &app.popup_details;
         let popup_area = popup_area(frame.area(), popup_details.width, popup_details.height);
-        let popup = get_popup(popup_details);
+        let popup = get_popup(popup_details, app.theme.popup, app.theme.border);
         frame.render_widget(Clear, popup_area);
         frame.render_widget(popup, popup_area);
     }
+
+    if app.get_active_component() == UiComponent::ProblemsPopup {
+        render_problems_popup(app, frame);
+    }
+
+    if app.get_active_component() == UiComponent::SessionSummary {
+        render_session_summary_popup(app, frame);
+    }
+
+    if app.get_active_component() == UiComponent::DeckNameInput {
+        render_deck_name_popup(app, frame);
+    }
+
+    if app.get_active_component() == UiComponent::HelpPopup {
+        render_help_popup(app, frame);
+    }
+}
+
+fn render_deck_name_popup(app: &mut AppState, frame: &mut Frame) {
+    let area = frame.area();
+    let popup_area = popup_area(area, area.width.saturating_sub(4).min(60), 3);
+    let width = popup_area.width.max(4) - 4;
+    let scroll = app.deck_name_input.visual_scroll(width as usize);
+    let input = Paragraph::new(app.deck_name_input.value().to_string())
+        .scroll((0, scroll as u16))
+        .block(Block::bordered()
+            .title(format!("[ Save {} marked questions as a deck ]", app.marked_questions.len()))
+            .title_bottom(format!(" {} ", app.keymap.get_help_text(&[
+                (&[Action::Confirm], "save".to_string()),
+                (&[Action::Back], "cancel".to_string()),
+            ])))
+            .padding(Padding::horizontal(1))
+            .border_type(BorderType::Thick)
+            .border_style(app.theme.border)
+        );
+    let cursor = app.deck_name_input.visual_cursor().max(scroll) - scroll + 1;
+
+    frame.render_widget(Clear, popup_area);
+    frame.render_widget(input, popup_area);
+    frame.set_cursor_position((popup_area.x + 1 + cursor as u16, popup_area.y + 1));
+}
+
+fn render_session_summary_popup(app: &mut AppState, frame: &mut Frame) {
+    let Some(summary) = &app.session_summary else { return };
+    let area = frame.area();
+    let popup_area = popup_area(area, area.width.saturating_sub(4).min(80), area.height.saturating_sub(4));
+    let report_lines = summary.get_report_lines();
+    // Stop scrolling once the last line is visible.
+    let max_scroll = (report_lines.len() as u16).saturating_sub(popup_area.height.saturating_sub(3));
+    app.session_summary_scroll = app.session_summary_scroll.min(max_scroll);
+    let lines = report_lines.into_iter()
+        .map(|line| {
+            if line.ends_with(':') { Line::from(line).style(app.theme.header) } else { Line::from(line) }
+        })
+        .collect::<Vec<Line>>();
+    let paragraph = Paragraph::new(lines)
+        .scroll((app.session_summary_scroll, 0))
+        .block(Block::bordered()
+            .title("[ Session summary ]")
+            .title_bottom(format!(" {} ", app.keymap.get_help_text(&[
+                (&[Action::RetryMissed], "practice missed".to_string()),
+                (&[Action::ExportSummary], "export".to_string()),
+                (&[Action::MoveUp, Action::MoveDown], "scroll".to_string()),
+                (&[Action::Confirm, Action::Back], "back to setup".to_string()),
+            ])))
+            .padding(Padding::new(1, 1, 1, 0))
+            .border_type(BorderType::Thick)
+            .border_style(app.theme.border)
+        );
+
+    frame.render_widget(Clear, popup_area);
+    frame.render_widget(paragraph, popup_area);
+}
+
+fn render_help_popup(app: &mut AppState, frame: &mut Frame) {
+    let component = app.get_help_component();
+    let help_lines = help::get_help_lines(component, &app.keymap, &app.config.penalties);
+    let area = frame.area();
+    let popup_area = popup_area(area, area.width.saturating_sub(4), area.height.saturating_sub(2));
+    // Stop scrolling once the last line is visible, counting the wrapped lines too.
+    let text_width = popup_area.width.saturating_sub(4).max(1) as usize;
+    let wrapped_line_count: usize = help_lines.iter()
+        .map(|line| line.chars().count().div_ceil(text_width).max(1))
+        .sum();
+    let max_scroll = (wrapped_line_count as u16).saturating_sub(popup_area.height.saturating_sub(3));
+    app.help_scroll = app.help_scroll.min(max_scroll);
+    let lines = help_lines.into_iter()
+        .map(|line| {
+            if line.ends_with(':') { Line::from(line).style(app.theme.header) } else { Line::from(line) }
+        })
+        .collect::<Vec<Line>>();
+    let paragraph = Paragraph::new(lines)
+        .wrap(Wrap { trim: false })
+        .scroll((app.help_scroll, 0))
+        .block(Block::bordered()
+            .title(format!("[ Help: {} ]", help::get_component_title(app.get_help_component())))
+            .title_bottom(format!(" {} ", app.keymap.get_help_text(&[
+                (&[Action::MoveUp, Action::MoveDown], "scroll".to_string()),
+                (&[Action::Back], "close".to_string()),
+            ])))
+            .padding(Padding::new(1, 1, 1, 0))
+            .border_type(BorderType::Thick)
+            .border_style(app.theme.border)
+        );
+
+    frame.render_widget(Clear, popup_area);
+    frame.render_widget(paragraph, popup_area);
+}
+
+fn render_problems_popup(app: &mut AppState, frame: &mut Frame) {
+    let area = frame.area();
+    let popup_area = popup_area(area, area.width.saturating_sub(4), area.height.saturating_sub(4));
+    let problems = app.diagnostics.iter()
+        .map(|diagnostic| {
+            let style = match diagnostic.severity {
+                Severity::Warning => app.theme.warning,
+                Severity::Error => app.theme.error,
+            };
+            ListItem::new(diagnostic.to_string()).style(style)
+        })
+        .collect::<Vec<ListItem>>();
+    let problems_len = problems.len();
+    let list = List::new(problems)
+        .block(Block::bordered()
+            .title(format!("[ Problems: {} ]", problems_len))
+            .title_bottom(format!(" {} ", app.keymap.get_help_text(&[
+                (&[Action::MoveUp, Action::MoveDown], "scroll".to_string()),
+                (&[Action::Back], "close".to_string()),
+            ])))
+            .padding(Padding::horizontal(1))
+            .border_type(BorderType::Thick)
+            .border_style(app.theme.border)
+        )
+        .highlight_symbol("> ")
+        .highlight_style(app.theme.highlight)
+        .scroll_padding(1);
+
+    frame.render_widget(Clear, popup_area);
+    frame.render_stateful_widget(list, popup_area, &mut app.problems_list_state);
+    frame.render_stateful_widget(
+        ui_shared::get_new_scrollbar(),
+        popup_area.inner(Margin {vertical: 1, horizontal: 0}), // Draw inside the same area
+        &mut ScrollbarState::new(problems_len)
+            .position(app.problems_list_state.selected().unwrap_or(0)),
+    );
 }
 
-fn get_popup<'a>(details: &PopupDetails) -> Paragraph<'a> {
+fn get_popup<'a>(details: &PopupDetails, style: Style, border_style: Style) -> Paragraph<'a> {
     Paragraph::new(details.message.clone())
         .block(Block::bordered()
             .title(details.title.clone())
             .padding(Padding::horizontal(1))
             .padding(Padding::vertical(1))
             .border_type(BorderType::Thick)
-            .style(Style::default().fg(Color::Red))
+            .border_style(border_style)
+            .style(style)
         ).alignment(Alignment::Center)
 }
 
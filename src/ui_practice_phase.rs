@@ -1,19 +1,39 @@
 use ratatui::Frame;
 use ratatui::layout::{Constraint, Layout, Rect};
-use ratatui::prelude::{Color, Style};
+use ratatui::prelude::Style;
+use ratatui::text::{Line, Span};
 use ratatui_widgets::block::{Block, Padding};
 use ratatui_widgets::borders::BorderType;
 use ratatui_widgets::list::{List, ListItem};
 use ratatui_widgets::paragraph::Paragraph;
 use strum::{EnumMessage, IntoEnumIterator};
+use std::time::Instant;
+use ratatui_widgets::gauge::Gauge;
 use crate::app::{AppState, PracticeControlOptions, UiComponent};
+use crate::blitz::Blitz;
+use crate::keymap::Action;
+use crate::practice::{AnswerMode, CharacterDiff, Direction};
+use crate::theme::Theme;
 use crate::{ui_shared};
 
-pub(crate) fn render_title_with_help_text(frame: &mut Frame, title_block: Block, draw_area: Rect) {
+pub(crate) fn render_title_with_help_text(app: &mut AppState, frame: &mut Frame, title_block: Block, draw_area: Rect) {
+    let answer_help_text = match app.answer_mode {
+        AnswerMode::Typing => app.keymap.get_help_text(&[(&[Action::Confirm], "check answer".to_string())]),
+        AnswerMode::MultipleChoice => app.keymap.get_help_text(&[(&[Action::PickOption], "pick answer".to_string())]),
+    };
+    let help_text = app.keymap.get_help_text(&[
+        (&[Action::ShowHelp], "help".to_string()),
+        (&[Action::SwitchPanes], "switch panes".to_string()),
+        (&[Action::MoveUp, Action::MoveDown], "navigate menu".to_string()),
+        (&[Action::Select], "choose menu item".to_string()),
+    ]);
+    let resize_help_text = app.keymap.get_help_text(&[
+        (&[Action::GrowPane, Action::ResetPaneSize, Action::ShrinkPane], "resize".to_string()),
+        (&[Action::Back], "quit".to_string()),
+    ]);
     frame.render_widget(
-        Paragraph::new(
-            "[Tab] switch panes, [↑↓] navigate menu, [Enter] check answer, [+/#/-] resize, [Esc] quit "
-        ).block(title_block).style(Style::new().dim()),
+        Paragraph::new(format!("{help_text}, {answer_help_text}, {resize_help_text} "))
+            .block(title_block).style(app.theme.hint),
 
         draw_area
     );
@@ -31,7 +51,29 @@ fn get_practice_control_list<'a>(app: &mut AppState) -> List<'a>{
     let (border, style) = ui_shared::get_style_for_component(UiComponent::PracticeControls, app);
     let controls = PracticeControlOptions::iter()
         .map(|control | {
-            let readable_control_name = control.get_message().unwrap().to_string();
+            let readable_control_name = match control {
+                PracticeControlOptions::Direction => format!(
+                    "{}: {}",
+                    control.get_message().unwrap(),
+                    app.direction_setting.get_message().unwrap()
+                ),
+                PracticeControlOptions::Accents => format!(
+                    "{}: {}",
+                    control.get_message().unwrap(),
+                    app.grading_settings.diacritics_mode.get_message().unwrap()
+                ),
+                PracticeControlOptions::MasteryStreak => format!(
+                    "{}: {}",
+                    control.get_message().unwrap(),
+                    app.mastery_streak
+                ),
+                PracticeControlOptions::AnswerMode => format!(
+                    "{}: {}",
+                    control.get_message().unwrap(),
+                    app.answer_mode.get_message().unwrap()
+                ),
+                _ => control.get_message().unwrap().to_string(),
+            };
             ListItem::new(readable_control_name).style(style)
         })
         .collect::<Vec<ListItem>>();
@@ -41,35 +83,58 @@ fn get_practice_control_list<'a>(app: &mut AppState) -> List<'a>{
         Block::bordered()
             .padding(Padding::horizontal(1))
             .border_type(border)
-            .border_style(Style::new().dim())
+            .border_style(app.theme.border)
     )
         .highlight_symbol("> ")
-        .highlight_style(style.fg(Color::Black).bg(Color::White))
+        .highlight_style(style.patch(app.theme.highlight))
         .scroll_padding(1)
 }
 
 pub(crate) fn render_practice_main(app: &mut AppState, frame: &mut Frame, draw_area: Rect) {
     let (border, style) = ui_shared::get_style_for_component(UiComponent::PracticeMain, app);
     let [
+        main_blitz_area,
         main_question_area,
         main_answer_area,
+        main_diff_area,
         main_status_area
     ] = Layout::vertical([
+        Constraint::Length(if app.blitz.is_some() { 3 } else { 0 }),
         Constraint::Length(3),
-        Constraint::Length(3),
+        Constraint::Length(match app.answer_mode {
+            AnswerMode::Typing => 3,
+            AnswerMode::MultipleChoice => app.answer_options.len() as u16 + 2,
+        }),
+        Constraint::Length(if app.answer_diff.is_some() { 3 } else { 0 }),
         Constraint::Length(3)
     ])
         .areas(draw_area);
 
 
-    let question = app.practice_get_current_question_in_round()
-        .borrow_mut()
-        .clone();
-    let question_text = question.question;
+    let question_text = app.practice_get_current_prompt();
+    let question_title = match app.current_direction {
+        Direction::Forward => " Question: ",
+        Direction::Reverse => " Question (reverse): ",
+    };
 
-    frame.render_widget(get_generic_block(" Question: ", question_text, style, border), main_question_area);
-    render_input(app, frame, style, border, main_answer_area);
-    frame.render_widget(get_generic_block(" Round status: ", app.practice_get_round_status_string(), style, border), main_status_area);
+    if let Some(blitz) = &app.blitz {
+        frame.render_widget(get_blitz_gauge(blitz, &app.theme, style, border), main_blitz_area);
+    }
+    frame.render_widget(get_generic_block(question_title, question_text, &app.theme, style, border), main_question_area);
+    match app.answer_mode {
+        AnswerMode::Typing => render_input(app, frame, style, border, main_answer_area),
+        AnswerMode::MultipleChoice => frame.render_widget(get_answer_options_block(app, style, border), main_answer_area),
+    }
+    if let Some(diff) = &app.answer_diff {
+        frame.render_widget(get_answer_diff_block(diff, &app.theme, style, border), main_diff_area);
+    }
+    // Color the status after the previous attempt, unless it shows the revealed clue or answer.
+    let status_style = if app.revealed_variants.is_some() {
+        style
+    } else {
+        style.patch(app.theme.get_feedback_style(app.previous_attempt_result.as_ref()))
+    };
+    frame.render_widget(get_generic_block(" Round status: ", app.practice_get_round_status_string(), &app.theme, status_style, border), main_status_area);
 
 }
 
@@ -83,7 +148,7 @@ fn render_input(app: &mut AppState, frame: &mut Frame, style: Style, border: Bor
             .title(" Answer: ")
             .border_type(border)
             .padding(Padding::horizontal(1))
-            .border_style(Style::new().dim())
+            .border_style(app.theme.border)
         );
 
     let cursor = app.answer_input.visual_cursor().max(scroll) - scroll + 1;
@@ -92,7 +157,79 @@ fn render_input(app: &mut AppState, frame: &mut Frame, style: Style, border: Bor
     frame.render_widget(input, area);
 }
 
-fn get_generic_block(title: &'_ str, contents: String, style: Style, border: BorderType) -> Paragraph<'_> {
+fn get_blitz_gauge<'a>(blitz: &Blitz, theme: &Theme, style: Style, border: BorderType) -> Gauge<'a> {
+    let now = Instant::now();
+    let remaining_time = blitz.get_remaining_time(now);
+    let gauge_style = if remaining_time.as_secs() < 10 { theme.wrong } else { theme.correct };
+    Gauge::default()
+        .ratio(blitz.get_remaining_ratio(now).clamp(0.0, 1.0))
+        .label(format!(
+            "{}s left, card: {}s, correct: {}, missed: {}",
+            remaining_time.as_secs(),
+            blitz.get_remaining_card_time(now).as_secs(),
+            blitz.correct_count,
+            blitz.missed_count
+        ))
+        .gauge_style(style.patch(gauge_style))
+        .block(
+            Block::bordered()
+                .title(" Blitz: ")
+                .padding(Padding::horizontal(1))
+                .border_type(border)
+                .border_style(theme.border)
+        )
+}
+
+fn get_answer_options_block<'a>(app: &AppState, style: Style, border: BorderType) -> Paragraph<'a> {
+    let keys = app.keymap.get_bindings(Action::PickOption);
+    let options = app.answer_options.iter()
+        .enumerate()
+        .map(|(index, option)| match keys.get(index) {
+            Some(key) => Line::from(format!("[{key}] {option}")),
+            None => Line::from(option.clone()),
+        })
+        .collect::<Vec<Line>>();
+    Paragraph::new(options)
+        .style(style)
+        .block(
+            Block::bordered()
+                .title(" Answer: ")
+                .padding(Padding::horizontal(1))
+                .border_type(border)
+                .border_style(app.theme.border)
+        )
+}
+
+fn get_answer_diff_block<'a>(diff: &[CharacterDiff], theme: &Theme, style: Style, border: BorderType) -> Paragraph<'a> {
+    let spans = diff.iter()
+        .map(|character| match character {
+            CharacterDiff::Matching(c) => Span::styled(c.to_string(), style),
+            CharacterDiff::Missing(c) => Span::styled(c.to_string(), style.patch(theme.correct).underlined()),
+            CharacterDiff::Extra(c) => Span::styled(c.to_string(), style.patch(theme.wrong).crossed_out()),
+            CharacterDiff::Wrong { expected, .. } => Span::styled(expected.to_string(), style.patch(theme.almost).bold()),
+        })
+        .collect::<Vec<Span>>();
+    Paragraph::new(Line::from(spans))
+        .style(style)
+        .block(
+            Block::bordered()
+                .title(" Difference: ")
+                .title(Line::from(vec![
+                    Span::raw(" "),
+                    Span::styled("missing", theme.correct.underlined()),
+                    Span::raw(" "),
+                    Span::styled("extra", theme.wrong.crossed_out()),
+                    Span::raw(" "),
+                    Span::styled("wrong", theme.almost.bold()),
+                    Span::raw(" "),
+                ]).right_aligned())
+                .padding(Padding::horizontal(1))
+                .border_type(border)
+                .border_style(theme.border)
+        )
+}
+
+fn get_generic_block<'a>(title: &'a str, contents: String, theme: &Theme, style: Style, border: BorderType) -> Paragraph<'a> {
     Paragraph::new(contents)
         .style(style)
         .block(
@@ -100,6 +237,6 @@ fn get_generic_block(title: &'_ str, contents: String, style: Style, border: Bor
                 .title(title)
                 .padding(Padding::horizontal(1))
                 .border_type(border)
-                .border_style(Style::new().dim())
+                .border_style(theme.border)
         )
 }
\ No newline at end of file
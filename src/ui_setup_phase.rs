@@ -1,24 +1,89 @@
+use std::collections::HashSet;
+use std::path::Path;
 use ratatui::Frame;
 use ratatui::layout::{Constraint, Margin, Rect};
-use ratatui::prelude::{Color};
 use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+use ratatui_widgets::barchart::{Bar, BarChart};
 use ratatui_widgets::block::{Block, Padding};
 use ratatui_widgets::list::{List, ListItem};
 use ratatui_widgets::paragraph::Paragraph;
 use ratatui_widgets::table::{Row, Table};
+use strum::EnumMessage;
 use crate::app::{AppState, UiComponent};
+use crate::group_tree;
+use crate::keymap::Action;
+use crate::group_tree::{GroupTreeRow, GroupTreeRowKind};
+use crate::practice;
+use crate::question::Question;
+use crate::question_parser::QuestionGroup;
 use crate::ui_shared;
 
-pub(crate) fn render_title_with_help_text(frame: &mut Frame, title_block: Block, draw_area: Rect) {
+pub(crate) fn render_title_with_help_text(app: &mut AppState, frame: &mut Frame, title_block: Block, draw_area: Rect) {
+    if app.get_active_component() == UiComponent::SearchInput || app.search_query.is_some() {
+        return render_search_bar(app, frame, draw_area);
+    }
+    let help_text = app.keymap.get_help_text(&[
+        (&[Action::ShowHelp], "help".to_string()),
+        (&[Action::SwitchPanes], "switch panes".to_string()),
+        (&[Action::MoveUp, Action::MoveDown], "navigate".to_string()),
+        (&[Action::MoveLeft, Action::MoveRight], "collapse/expand folders".to_string()),
+        (&[Action::Select], "select groups or folders, mark questions".to_string()),
+        (&[Action::InvertMarks], "invert marks".to_string()),
+        (&[Action::SaveDeck], "save marked as deck".to_string()),
+        (&[Action::Search], "search".to_string()),
+        (&[Action::StartPractice], "start practice".to_string()),
+        (&[Action::ToggleDirection], format!("direction: {}", app.direction_setting.get_message().unwrap_or_default())),
+        (&[Action::ToggleAnswerMode], format!("answers: {}", app.answer_mode.get_message().unwrap_or_default())),
+        (&[Action::ToggleAccents], format!("accents: {}", app.grading_settings.diacritics_mode.get_message().unwrap_or_default())),
+        (&[Action::ToggleGroupAccents], "group accents".to_string()),
+        (&[Action::ShowProblems], format!("problems: {}", app.diagnostics.len())),
+        (&[Action::GrowPane, Action::ResetPaneSize, Action::ShrinkPane], "resize".to_string()),
+        (&[Action::Back], "quit".to_string()),
+    ]);
     frame.render_widget(
-        Paragraph::new(
-            "[Tab] switch panes, [↑↓←→] navigate, [Enter/Space] select groups, [P] start practice, [+/#/-] resize, [Esc] quit"
-        ).block(title_block).style(Style::new().dim()),
-
+        Paragraph::new(help_text).block(title_block).style(app.theme.hint),
         draw_area
     );
 }
 
+fn render_search_bar(app: &mut AppState, frame: &mut Frame, draw_area: Rect) {
+    let is_typing = app.get_active_component() == UiComponent::SearchInput;
+    let hints = if is_typing {
+        app.keymap.get_help_text(&[
+            (&[Action::Confirm], "confirm".to_string()),
+            (&[Action::ToggleSearchMode], "text/regex".to_string()),
+            (&[Action::Back], "clear".to_string()),
+        ])
+    } else {
+        app.keymap.get_help_text(&[
+            (&[Action::Search], "edit".to_string()),
+            (&[Action::PreviousSearchHit, Action::NextSearchHit], "previous/next hit".to_string()),
+            (&[Action::SelectSearchHits], "select matching groups".to_string()),
+        ])
+    };
+    let width = draw_area.width.max(4) - 4;
+    let scroll = app.search_input.visual_scroll(width as usize);
+    let search_bar = Paragraph::new(app.search_input.value().to_string())
+        .scroll((0, scroll as u16))
+        .style(if is_typing { app.theme.focused } else { app.theme.unfocused })
+        .block(
+            Block::bordered()
+                .title(format!("[ Search ({}) ]", app.search_mode.get_message().unwrap_or_default()))
+                .title_bottom(Line::from(match &app.search_error {
+                    Some(error) => Span::styled(format!(" {error} "), app.theme.error),
+                    None => Span::styled(format!(" {hints} "), app.theme.hint),
+                }).right_aligned())
+                .border_style(app.theme.border)
+                .padding(Padding::horizontal(1))
+        );
+    if is_typing {
+        let cursor = app.search_input.visual_cursor().max(scroll) - scroll + 1;
+        frame.set_cursor_position((draw_area.x + 1 + cursor as u16, draw_area.y + 1));
+    }
+    frame.render_widget(search_bar, draw_area);
+}
+
 pub(crate) fn render_group_list_with_scrollbar(app: &mut AppState, frame: &mut Frame, draw_area: Rect) {
     let (groups_list, groups_list_len) = get_question_group_list(app);
     frame.render_stateful_widget(
@@ -37,31 +102,111 @@ pub(crate) fn render_group_list_with_scrollbar(app: &mut AppState, frame: &mut F
 fn get_question_group_list<'a>(app: &mut AppState) -> (List<'a>, usize) {
     let (border, style) = ui_shared::get_style_for_component(UiComponent::GroupSelector, app);
     let list = List::new(
-        app.questions_by_groups
+        app.group_tree_rows
             .iter()
-            .map(| (group_name, group_details)| {
-                let selection_postfix = if group_details.is_active { " *"} else { "" };
-                ListItem::new(format!("{}{}",group_name.clone(), selection_postfix))
-                    .style(
-                        if group_details.is_active { style.bold().fg(Color::Green) }
-                        else { style }
-                    )
-            })
+            .map(|row| get_group_tree_list_item(app, row, style))
     )
         .block(
             Block::bordered()
                 .padding(Padding::horizontal(1))
                 .border_type(border)
-                .border_style(Style::new().dim())
+                .border_style(app.theme.border)
         )
         .highlight_symbol("> ")
-        .highlight_style(style.fg(Color::Black).bg(Color::White))
+        .highlight_style(style.patch(app.theme.highlight))
         .scroll_padding(1);
 
     let list_len = list.len();
     (list, list_len)
 }
 
+fn get_group_tree_list_item<'a>(app: &AppState, row: &GroupTreeRow, style: Style) -> ListItem<'a> {
+    let indentation = "  ".repeat(row.depth);
+    match row.kind {
+        GroupTreeRowKind::Folder { is_expanded } => {
+            let groups_in_folder: Vec<&QuestionGroup> = app.questions_by_groups.iter()
+                .filter(|(group_name, _)| group_tree::is_in_folder(group_name, &row.path))
+                .map(|(_, group)| group)
+                .collect();
+            let active_count = groups_in_folder.iter().filter(|group| group.is_active).count();
+            let expansion_symbol = if is_expanded { "▾" } else { "▸" };
+            ListItem::new(format!(
+                "{indentation}{expansion_symbol} {}/ ({active_count}/{})",
+                row.get_label(),
+                groups_in_folder.len()
+            ))
+                .style(
+                    if active_count > 0 { style.patch(app.theme.active) }
+                    else { style }
+                )
+        },
+        GroupTreeRowKind::Group => {
+            let Some(group_details) = app.questions_by_groups.get(&row.path) else { return ListItem::new(indentation) };
+            let selection_postfix = if group_details.is_active { " *"} else { "" };
+            let deck_postfix = if group_details.deck.is_some() { " [deck]" } else { "" };
+            let accents_postfix = group_details.diacritics_mode
+                .map(|mode| format!(" [accents: {}]", mode.get_message().unwrap_or_default()))
+                .unwrap_or_default();
+            let mut line = ui_shared::get_line_with_search_matches(row.get_label().to_string(), app.search_query.as_ref(), app.theme.search_match);
+            line.spans.insert(0, Span::raw(indentation));
+            line.push_span(format!("{}{}{}", deck_postfix, selection_postfix, accents_postfix));
+            ListItem::new(line)
+                .style(
+                    if group_details.is_active { style.bold().patch(app.theme.active) }
+                    else { style }
+                )
+        },
+    }
+}
+
+/// Returns the answers with the files they were read from, e.g. `➔ "pies", "psa" [dog.sll]`
+fn get_answers_with_sources(question: &Question) -> String {
+    let answers_by_locations = question.get_answers_by_locations();
+    let file_names: Vec<String> = answers_by_locations.keys()
+        .map(|location| Path::new(location).file_name().unwrap_or_default().to_string_lossy().to_string())
+        .collect();
+    // The same file name can come from different folders if the files with the same name are merged.
+    let has_unique_file_names = file_names.iter().collect::<HashSet<_>>().len() == file_names.len();
+    let answers_with_sources = answers_by_locations.iter()
+        .zip(file_names)
+        .map(|((location, answers), file_name)| {
+            let answers = answers.iter()
+                .map(|answer| format!("\"{answer}\""))
+                .collect::<Vec<String>>()
+                .join(", ");
+            match (location.is_empty(), has_unique_file_names) {
+                (true, _) => answers,
+                (false, true) => format!("{answers} [{file_name}]"),
+                (false, false) => format!("{answers} [{location}]"),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(", ");
+    format!("➔ {answers_with_sources}")
+}
+
+pub(crate) fn render_leitner_box_chart(app: &mut AppState, frame: &mut Frame, draw_area: Rect) {
+    let box_counts = practice::get_leitner_box_counts(
+        &app.practice_get_all_active_questions(),
+        app.direction_setting.get_directions(),
+    );
+    let bars = box_counts.iter()
+        .enumerate()
+        .map(|(index, count)| Bar::with_label(format!("Box {}", index + 1), *count))
+        .collect::<Vec<Bar>>();
+    let chart = BarChart::horizontal(bars)
+        .bar_gap(0)
+        .bar_style(app.theme.active)
+        .value_style(app.theme.active_value)
+        .block(
+            Block::bordered()
+                .title(" Leitner boxes of the active groups: ")
+                .padding(Padding::horizontal(1))
+                .border_style(app.theme.border)
+        );
+    frame.render_widget(chart, draw_area);
+}
+
 pub(crate) fn render_question_table_with_scrollbar(app: &mut AppState, frame: &mut Frame, draw_area: Rect) {
     let (questions_table, questions_table_len) = get_question_table(app);
     frame.render_stateful_widget(
@@ -79,25 +224,30 @@ pub(crate) fn render_question_table_with_scrollbar(app: &mut AppState, frame: &m
 
 fn get_question_table<'a>(app: &mut AppState) -> (Table<'a>, usize) {
     let (border, style) = ui_shared::get_style_for_component(UiComponent::QuestionSelector, app);
+    let directions = app.direction_setting.get_directions();
     let rows = app.setup_get_questions_for_selected_group()
         .into_iter()
         .map(|q| Row::new([
-            q.borrow().question.clone(), {
-                format!("➔ {:?}", q.borrow().answers)
-                    .replace("{", "")
-                    .replace("}", "")
-            }
+            Line::from(if app.setup_is_question_marked(&q.borrow()) { "✓" } else { "" }).style(app.theme.active),
+            ui_shared::get_line_with_search_matches(q.borrow().question.clone(), app.search_query.as_ref(), app.theme.search_match),
+            ui_shared::get_line_with_search_matches(get_answers_with_sources(&q.borrow()), app.search_query.as_ref(), app.theme.search_match),
+            Line::from(practice::get_average_response_time(&q.borrow(), directions)
+                .map(|time| format!("{:.1}s", time.as_secs_f32()))
+                .unwrap_or_default()),
         ]));
     let question_count = rows.len();
-    let column_widths = [Constraint::Fill(1), Constraint::Fill(1)];
+    let column_widths = [Constraint::Length(1), Constraint::Fill(1), Constraint::Fill(1), Constraint::Length(8)];
+    let marked_count = app.marked_questions.len();
     let table = Table::new(rows, column_widths)
+        .header(Row::new(["", "Question", "Answers", "Avg time"]).style(app.theme.hint.underlined()))
         .block(
             Block::bordered()
+                .title(if marked_count > 0 { format!(" Marked for practice: {marked_count} ") } else { String::new() })
                 .padding(Padding::horizontal(1))
                 .border_type(border)
-                .border_style(Style::new().dim())
+                .border_style(app.theme.border)
         )
-        .row_highlight_style(style.fg(Color::Black).bg(Color::White)).style(style);
+        .row_highlight_style(style.patch(app.theme.highlight)).style(style);
 
     (table, question_count)
 }
@@ -1,13 +1,15 @@
 use ratatui::style::Style;
+use ratatui::text::{Line, Span};
 use ratatui_widgets::borders::BorderType;
 use ratatui_widgets::scrollbar::{Scrollbar, ScrollbarOrientation};
 use crate::app::{AppState, UiComponent};
+use crate::search::SearchQuery;
 
 pub(crate) fn get_style_for_component(component: UiComponent, app: &mut AppState) -> (BorderType, Style) {
     if app.get_active_component() == component {
-        (BorderType::Thick, Style::default())
+        (BorderType::Thick, app.theme.focused)
     } else {
-        (BorderType::Plain, Style::default().dim())
+        (BorderType::Plain, app.theme.unfocused)
     }
 }
 
@@ -16,4 +18,18 @@ pub(crate) fn get_new_scrollbar<'a>() -> Scrollbar<'a>  {
         .track_symbol(None)
         .begin_symbol(None)
         .end_symbol(None)
-}
\ No newline at end of file
+}
+
+/// Returns the text as a line with the search matches highlighted in the given style.
+pub(crate) fn get_line_with_search_matches<'a>(text: String, search_query: Option<&SearchQuery>, match_style: Style) -> Line<'a> {
+    let Some(query) = search_query else { return Line::from(text) };
+    let mut spans = vec![];
+    let mut last_end = 0;
+    for range in query.find_matches(&text) {
+        spans.push(Span::raw(text[last_end..range.start].to_string()));
+        spans.push(Span::styled(text[range.clone()].to_string(), match_style));
+        last_end = range.end;
+    }
+    spans.push(Span::raw(text[last_end..].to_string()));
+    Line::from(spans)
+}